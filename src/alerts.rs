@@ -0,0 +1,313 @@
+use std::{
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    app::Pane,
+    collector::{DiskInfo, Snapshot},
+    config::{AlertConfig, AlertMetric},
+};
+
+const MB: f64 = 1_000_000.0;
+
+impl AlertMetric {
+    /// The pane showing the metric.
+    pub fn pane(self) -> Pane {
+        match self {
+            AlertMetric::Cpu | AlertMetric::CoreCpu => Pane::Cpu,
+            AlertMetric::Mem => Pane::Memory,
+            AlertMetric::DiskRead | AlertMetric::DiskWrite => Pane::Disks,
+            AlertMetric::ProcessCpu | AlertMetric::ProcessMem => Pane::Processes,
+        }
+    }
+
+    /// The name used for the metric in the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertMetric::Cpu => "cpu",
+            AlertMetric::CoreCpu => "core_cpu",
+            AlertMetric::Mem => "mem",
+            AlertMetric::DiskRead => "disk_read",
+            AlertMetric::DiskWrite => "disk_write",
+            AlertMetric::ProcessCpu => "process_cpu",
+            AlertMetric::ProcessMem => "process_mem",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            AlertMetric::Cpu
+            | AlertMetric::CoreCpu
+            | AlertMetric::Mem
+            | AlertMetric::ProcessCpu => "%",
+            AlertMetric::DiskRead | AlertMetric::DiskWrite => "MB/s",
+            AlertMetric::ProcessMem => "MB",
+        }
+    }
+}
+
+/// The value of the alert's metric in the snapshot. `None` if the snapshot doesn't contain
+/// it, e.g. when the configured core doesn't exist.
+fn value(config: &AlertConfig, snapshot: &Snapshot) -> Option<f64> {
+    // 0 for reads, 1 for writes
+    let disk_rate = |direction: usize| {
+        let rate = |info: &DiskInfo| info.bytes_per_second()[direction];
+
+        let bytes = match &config.disk {
+            Some(disk) => rate(snapshot.disks.get(disk)?),
+            None => snapshot.disks.values().map(rate).sum(),
+        };
+
+        Some(bytes / MB)
+    };
+
+    let processes = || {
+        snapshot
+            .processes
+            .iter()
+            .filter(|p| Some(&p.name) == config.process.as_ref())
+    };
+
+    match config.metric {
+        AlertMetric::Cpu if snapshot.cpus.is_empty() => None,
+        AlertMetric::Cpu => Some(snapshot.cpus.iter().sum::<f64>() / snapshot.cpus.len() as f64),
+
+        AlertMetric::CoreCpu => match config.core {
+            Some(core) => snapshot.cpus.get(core).copied(),
+            None => snapshot.cpus.iter().copied().reduce(f64::max),
+        },
+
+        AlertMetric::Mem if snapshot.mem_total == 0 => None,
+        AlertMetric::Mem => Some(snapshot.mem_used as f64 / snapshot.mem_total as f64 * 100.0),
+
+        AlertMetric::DiskRead => disk_rate(0),
+        AlertMetric::DiskWrite => disk_rate(1),
+
+        AlertMetric::ProcessCpu => Some(processes().map(|p| p.cpu).sum()),
+        AlertMetric::ProcessMem => Some(processes().map(|p| p.mem).sum::<u64>() as f64 / MB),
+    }
+}
+
+/// A configured alert together with its current state.
+pub struct Alert {
+    pub config: AlertConfig,
+    /// The latest value of the metric.
+    pub value: Option<f64>,
+    /// Timestamp of the first sample of the current streak at or above the threshold.
+    since: Option<Duration>,
+    pub active: bool,
+    /// Why the command couldn't be started the last time the alert triggered.
+    pub command_error: Option<String>,
+}
+
+impl Alert {
+    fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            value: None,
+            since: None,
+            active: false,
+            command_error: None,
+        }
+    }
+
+    /// Updates the state with a new value. Returns whether the alert just triggered.
+    fn update(&mut self, value: Option<f64>, timestamp: Duration) -> bool {
+        self.value = value;
+
+        if value.is_none_or(|v| v < self.config.threshold) {
+            self.since = None;
+            self.active = false;
+            return false;
+        }
+
+        let since = *self.since.get_or_insert(timestamp);
+        let triggered = !self.active
+            && timestamp.saturating_sub(since) >= Duration::from_secs(self.config.duration);
+        self.active |= triggered;
+
+        triggered
+    }
+
+    /// Starts the configured command without waiting for it, passing the alert in
+    /// environment variables.
+    fn run_command(&mut self) {
+        let Some(command) = &self.config.command else {
+            return;
+        };
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("JWTOP_ALERT_NAME", &self.config.name)
+            .env("JWTOP_ALERT_METRIC", self.config.metric.name())
+            .env(
+                "JWTOP_ALERT_VALUE",
+                format!("{:.2}", self.value.unwrap_or_default()),
+            )
+            .env("JWTOP_ALERT_THRESHOLD", self.config.threshold.to_string())
+            .env("JWTOP_ALERT_UNIT", self.config.metric.unit())
+            .env("JWTOP_ALERT_DURATION", self.config.duration.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match child {
+            Ok(mut child) => {
+                self.command_error = None;
+                // reaps the command once it exits
+                thread::spawn(move || child.wait());
+            }
+            Err(e) => self.command_error = Some(e.to_string()),
+        }
+    }
+
+    /// A short description of the alert's state.
+    pub fn describe(&self) -> String {
+        let unit = self.config.metric.unit();
+        let mut description = format!(
+            "{}: {:.1}{unit} >= {}{unit}",
+            self.config.name,
+            self.value.unwrap_or_default(),
+            self.config.threshold
+        );
+
+        if self.config.duration > 0 {
+            description.push_str(&format!(" for {}s", self.config.duration));
+        }
+
+        if let Some(error) = &self.command_error {
+            description.push_str(&format!(" (command failed: {error})"));
+        }
+
+        description
+    }
+}
+
+/// Every alert from the `[[alerts]]` config section.
+pub struct Alerts {
+    pub alerts: Vec<Alert>,
+}
+
+impl Alerts {
+    pub fn new(configs: Vec<AlertConfig>) -> Self {
+        Self {
+            alerts: configs.into_iter().map(Alert::new).collect(),
+        }
+    }
+
+    /// Evaluates every alert against a new snapshot, running the commands of the alerts
+    /// that triggered.
+    pub fn check(&mut self, snapshot: &Snapshot) {
+        for alert in &mut self.alerts {
+            let value = value(&alert.config, snapshot);
+
+            if alert.update(value, snapshot.timestamp) {
+                alert.run_command();
+            }
+        }
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = &Alert> {
+        self.alerts.iter().filter(|a| a.active)
+    }
+
+    /// Whether an active alert watches a metric shown in the pane.
+    pub fn alerted(&self, pane: Pane) -> bool {
+        self.active().any(|a| a.config.metric.pane() == pane)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(metric: AlertMetric, threshold: f64, duration: u64) -> AlertConfig {
+        AlertConfig {
+            name: "test".into(),
+            metric,
+            threshold,
+            duration,
+            core: None,
+            disk: None,
+            process: None,
+            command: None,
+        }
+    }
+
+    fn snapshot(secs: u64, cpus: Vec<f64>) -> Snapshot {
+        Snapshot {
+            timestamp: Duration::from_secs(secs),
+            cpus,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn durations() {
+        let mut alerts = Alerts::new(vec![config(AlertMetric::Cpu, 50.0, 2)]);
+
+        alerts.check(&snapshot(0, vec![60.0, 60.0]));
+        alerts.check(&snapshot(1, vec![60.0, 60.0]));
+        assert!(!alerts.alerted(Pane::Cpu));
+
+        alerts.check(&snapshot(2, vec![40.0, 80.0]));
+        assert!(alerts.alerted(Pane::Cpu));
+        assert!(!alerts.alerted(Pane::Memory));
+
+        alerts.check(&snapshot(3, vec![10.0, 80.0]));
+        assert!(!alerts.alerted(Pane::Cpu));
+    }
+
+    #[test]
+    fn values() {
+        let snapshot = snapshot(0, vec![20.0, 90.0]);
+
+        let mut core = config(AlertMetric::CoreCpu, 0.0, 0);
+        assert_eq!(value(&core, &snapshot), Some(90.0));
+        core.core = Some(0);
+        assert_eq!(value(&core, &snapshot), Some(20.0));
+        core.core = Some(2);
+        assert_eq!(value(&core, &snapshot), None);
+
+        let mem = config(AlertMetric::Mem, 0.0, 0);
+        assert_eq!(value(&mem, &snapshot), None);
+    }
+
+    #[test]
+    fn disk_rates() {
+        let disk = |r_sectors, w_sectors, elapsed_ms| DiskInfo {
+            r_sectors,
+            w_sectors,
+            elapsed_ms,
+            ..Default::default()
+        };
+        let snapshot = Snapshot {
+            // the disks were last read half a second ago, however long ago the snapshot was
+            timestamp: Duration::from_secs(10),
+            disks: [
+                ("sda".to_string(), disk(4000, 1000, 500)),
+                ("sdb".to_string(), disk(2000, 0, 1000)),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let mut read = config(AlertMetric::DiskRead, 5.0, 0);
+        assert_eq!(value(&read, &snapshot), Some(4.096 + 1.024));
+        read.disk = Some("sdb".into());
+        assert_eq!(value(&read, &snapshot), Some(1.024));
+        read.disk = Some("sdc".into());
+        assert_eq!(value(&read, &snapshot), None);
+
+        let write = config(AlertMetric::DiskWrite, 1.0, 0);
+        assert_eq!(value(&write, &snapshot), Some(1.024));
+
+        let mut alerts = Alerts::new(vec![config(AlertMetric::DiskRead, 5.0, 0)]);
+        alerts.check(&snapshot);
+        assert!(alerts.alerted(Pane::Disks));
+    }
+}
@@ -1,78 +1,35 @@
-use std::{collections::BTreeMap, collections::VecDeque, error, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    error,
+    time::Duration,
+};
 
-use regex::Regex;
-use sysinfo::{CpuExt, Pid, Process, ProcessExt, System, SystemExt};
-use systemstat::{BlockDeviceStats, Platform};
+use serde::{Deserialize, Serialize};
 
-use crate::{config::Config, ui::processes::Column};
+use crate::{
+    alerts::Alerts,
+    cgroups::Cgroups,
+    config::{ChartOptions, Config, Scope},
+    csv_log::CsvLogger,
+    exporter::Exporter,
+    hosts::Hosts,
+    recording::{Recorder, Replay},
+    remote::RemoteFeed,
+    state::State,
+    tiers::{Point, Store, Tiers, TIERS},
+    ui::{chart_wrapper::AxisTops, processes::Column, MAX_PLOTTED_CPUS},
+};
+
+pub use crate::collector::{
+    CgroupInfo, ChargeState, Collector, CpuTimes, DiskInfo, FilesystemInfo, PowerInfo, Pressure,
+    PressureStats, ProcessInfo, Snapshot,
+};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 pub const HISTORY_LEN: usize = 64;
 
-#[derive(Debug, Clone)]
-pub struct ProcessInfo {
-    pub pid: Pid,
-    pub cpu: f64,
-    pub mem: u64,
-    pub name: String,
-    pub disk_r: u64,
-    pub disk_w: u64,
-}
-
-impl ProcessInfo {
-    fn new(proc: &Process, cpu_amount: usize) -> Self {
-        Self {
-            pid: proc.pid(),
-            cpu: proc.cpu_usage() as f64 / cpu_amount as f64,
-            mem: proc.memory(),
-            name: proc.name().to_string(),
-            // FIXME: as per documentation, this is incorrect for FreeBSD and Windows
-            disk_r: proc.disk_usage().read_bytes,
-            disk_w: proc.disk_usage().written_bytes,
-        }
-    }
-}
-
-#[derive(Debug, Default, Clone, Copy)]
-pub struct DiskInfo {
-    pub r_sectors: usize,
-    pub w_sectors: usize,
-}
-
-impl DiskInfo {
-    fn new(stats: &BlockDeviceStats) -> Self {
-        Self {
-            r_sectors: stats.read_sectors,
-            w_sectors: stats.write_sectors,
-        }
-    }
-}
-
-struct DiskRegexes {
-    disks: Vec<Regex>,
-}
-
-impl DiskRegexes {
-    fn is_disk(&self, name: &str) -> bool {
-        self.disks.iter().any(|r| r.is_match(name))
-    }
-}
-
-impl Default for DiskRegexes {
-    fn default() -> Self {
-        Self {
-            disks: vec![
-                Regex::new(r"^nvme[0-9]+n[0-9]+$").unwrap(),
-                Regex::new(r"^sd[a-z]+$").unwrap(),
-                Regex::new(r"^hd[a-z]+$").unwrap(),
-                Regex::new(r"^disk[0-9]+$").unwrap(),
-            ],
-        }
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputState {
     ProcessesSortSelection {
@@ -86,6 +43,16 @@ pub enum InputState {
     },
 }
 
+impl InputState {
+    /// The key bindings scope active in this state.
+    pub fn scope(&self) -> Scope {
+        match self {
+            InputState::ProcessesSortSelection { .. } => Scope::Normal,
+            InputState::ProcessesSearch { .. } => Scope::Search,
+        }
+    }
+}
+
 impl Default for InputState {
     fn default() -> Self {
         let column = Column::default();
@@ -163,127 +130,824 @@ impl MemPrefix {
     }
 }
 
+/// The last snapshots, [`HISTORY_LEN`] of them unless zoomed out, split up by metric.
+#[derive(Debug, Clone)]
+pub struct History {
+    pub timestamps: VecDeque<Duration>,
+    pub cpus: Vec<VecDeque<f64>>,
+    /// Frequency of every cpu in MHz.
+    pub frequencies: Vec<VecDeque<f64>>,
+    /// Scaling governor of every cpu as of the newest sample.
+    pub governors: Vec<String>,
+    pub cpu_times: Vec<VecDeque<CpuTimes>>,
+    pub total_cpu_times: VecDeque<CpuTimes>,
+    /// Used memory in bytes.
+    pub mem: VecDeque<f64>,
+    /// Total memory in bytes, as of the newest sample.
+    pub mem_total: u64,
+    /// Load average as of the newest sample.
+    pub load_average: [f64; 3],
+    pub disks: BTreeMap<String, VecDeque<DiskInfo>>,
+    /// Mounted filesystems as of the newest sample.
+    pub filesystems: Vec<FilesystemInfo>,
+    /// Charge of the batteries in percent, 0 without batteries.
+    pub battery: VecDeque<f64>,
+    /// The batteries as of the newest sample.
+    pub power: Option<PowerInfo>,
+    /// The 10 second averages of the stall information, in the order of [`Pressure::NAMES`].
+    pub pressure_avg10: Vec<VecDeque<f64>>,
+    /// Pressure stall information as of the newest sample.
+    pub pressure: Option<Pressure>,
+    /// The cgroup v2 hierarchy as of the newest sample.
+    pub cgroups: Vec<CgroupInfo>,
+    pub processes: VecDeque<Vec<ProcessInfo>>,
+    /// Histories of the lowest and highest samples behind every point of a zoomed out
+    /// tier, `None` for samples shown as they were taken.
+    pub bounds: Option<Box<[History; 2]>>,
+}
+
+impl History {
+    pub fn new(cpus: usize) -> Self {
+        Self::with_len(cpus, HISTORY_LEN)
+    }
+
+    /// A history of `len` samples instead of [`HISTORY_LEN`].
+    pub fn with_len(cpus: usize, len: usize) -> Self {
+        Self {
+            timestamps: vec![Duration::ZERO; len].into(),
+            cpus: vec![vec![0.0; len].into(); cpus],
+            frequencies: vec![vec![0.0; len].into(); cpus],
+            governors: Vec::new(),
+            cpu_times: Vec::new(),
+            total_cpu_times: vec![CpuTimes::default(); len].into(),
+            mem: vec![0.0; len].into(),
+            mem_total: 0,
+            load_average: [0.0; 3],
+            disks: BTreeMap::new(),
+            filesystems: Vec::new(),
+            battery: vec![0.0; len].into(),
+            power: None,
+            pressure_avg10: vec![vec![0.0; len].into(); Pressure::NAMES.len()],
+            pressure: None,
+            cgroups: Vec::new(),
+            processes: vec![Vec::new(); len].into(),
+            bounds: None,
+        }
+    }
+
+    /// How many samples the history keeps.
+    pub fn capacity(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn push(&mut self, snapshot: Snapshot) {
+        fn push<T>(queue: &mut VecDeque<T>, value: T) {
+            queue.pop_front();
+            queue.push_back(value);
+        }
+
+        let len = self.capacity();
+        push(&mut self.timestamps, snapshot.timestamp);
+
+        self.cpus
+            .resize_with(snapshot.cpus.len(), || vec![0.0; len].into());
+        self.cpus
+            .iter_mut()
+            .zip(snapshot.cpus)
+            .for_each(|(history, cpu)| push(history, cpu));
+
+        self.frequencies
+            .resize_with(snapshot.frequencies.len(), || vec![0.0; len].into());
+        self.frequencies
+            .iter_mut()
+            .zip(snapshot.frequencies)
+            .for_each(|(history, frequency)| push(history, frequency as f64));
+        self.governors = snapshot.governors;
+
+        self.cpu_times.resize_with(snapshot.cpu_times.len(), || {
+            vec![CpuTimes::default(); len].into()
+        });
+        self.cpu_times
+            .iter_mut()
+            .zip(snapshot.cpu_times)
+            .for_each(|(history, times)| push(history, times));
+        push(&mut self.total_cpu_times, snapshot.total_cpu_times);
+
+        push(&mut self.mem, snapshot.mem_used as f64);
+        self.mem_total = snapshot.mem_total;
+        self.load_average = snapshot.load_average;
+
+        for name in snapshot.disks.keys() {
+            if !self.disks.contains_key(name) {
+                self.disks
+                    .insert(name.clone(), vec![Default::default(); len].into());
+            }
+        }
+        for (name, history) in &mut self.disks {
+            push(
+                history,
+                snapshot.disks.get(name).copied().unwrap_or_default(),
+            );
+        }
+
+        self.filesystems = snapshot.filesystems;
+        push(&mut self.battery, snapshot.power.map_or(0.0, |p| p.charge));
+        self.power = snapshot.power;
+        let stats = snapshot.pressure.unwrap_or_default().stats();
+        for (history, stats) in self.pressure_avg10.iter_mut().zip(stats) {
+            push(history, stats.avg10);
+        }
+        self.pressure = snapshot.pressure;
+        self.cgroups = snapshot.cgroups;
+        push(&mut self.processes, snapshot.processes);
+    }
+
+    /// Replaces the newest sample, like a point of a tier that is still collecting samples.
+    pub fn replace_newest(&mut self, snapshot: Snapshot) {
+        // drops the newest value, padding with a copy of the oldest that pushing drops again
+        fn unpush<T: Clone>(queue: &mut VecDeque<T>) {
+            if let Some(oldest) = queue.front().cloned() {
+                queue.pop_back();
+                queue.push_front(oldest);
+            }
+        }
+
+        unpush(&mut self.timestamps);
+        self.cpus.iter_mut().for_each(unpush);
+        self.frequencies.iter_mut().for_each(unpush);
+        self.cpu_times.iter_mut().for_each(unpush);
+        unpush(&mut self.total_cpu_times);
+        unpush(&mut self.mem);
+        self.disks.values_mut().for_each(unpush);
+        unpush(&mut self.battery);
+        self.pressure_avg10.iter_mut().for_each(unpush);
+        unpush(&mut self.processes);
+
+        self.push(snapshot);
+    }
+
+    /// Index of the sample `offset` samples before the newest one.
+    pub fn index(&self, offset: usize) -> usize {
+        let newest = self.capacity() - 1;
+        newest - offset.min(newest)
+    }
+
+    /// Time between the newest sample and the one `offset` samples before it.
+    pub fn age(&self, offset: usize) -> Duration {
+        let newest = self.timestamps.back().copied().unwrap_or_default();
+        let sample = self.timestamps[self.index(offset)];
+
+        if sample.is_zero() {
+            return Duration::ZERO;
+        }
+
+        newest.saturating_sub(sample)
+    }
+
+    /// The average time between the collected samples, `None` with fewer than two.
+    pub fn interval(&self) -> Option<Duration> {
+        let collected = self.collected();
+        if collected < 2 {
+            return None;
+        }
+
+        Some(self.age(collected - 1) / (collected - 1) as u32)
+    }
+
+    /// The amount of samples that were actually collected, as opposed to padding.
+    pub fn collected(&self) -> usize {
+        self.timestamps.iter().filter(|t| !t.is_zero()).count()
+    }
+}
+
+/// What the cpu chart plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuView {
+    #[default]
+    Usage,
+    Frequency,
+    /// Usage split up into user, system, iowait, irq and steal time.
+    Breakdown,
+    /// Every cpu as a row of cells colored by usage, one cell per sample.
+    Heatmap,
+}
+
+impl CpuView {
+    pub fn next(self) -> Self {
+        match self {
+            CpuView::Usage => CpuView::Frequency,
+            CpuView::Frequency => CpuView::Breakdown,
+            CpuView::Breakdown => CpuView::Heatmap,
+            CpuView::Heatmap => CpuView::Usage,
+        }
+    }
+}
+
+/// What the disks chart plots, for every disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskView {
+    /// Bytes read and written per second.
+    #[default]
+    Throughput,
+    /// Reads and writes completed per second.
+    Iops,
+    /// Average time a read and a write took.
+    Latency,
+    /// Share of the time the disk was busy, with the queue depth in the legend.
+    Utilization,
+}
+
+impl DiskView {
+    pub fn next(self) -> Self {
+        match self {
+            DiskView::Throughput => DiskView::Iops,
+            DiskView::Iops => DiskView::Latency,
+            DiskView::Latency => DiskView::Utilization,
+            DiskView::Utilization => DiskView::Throughput,
+        }
+    }
+}
+
+/// The panes of the usual layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Cpu,
+    /// Only shown on systems with batteries.
+    Battery,
+    Memory,
+    Disks,
+    Processes,
+    Filesystems,
+    Pressure,
+    Cgroups,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 8] = [
+        Pane::Cpu,
+        Pane::Battery,
+        Pane::Memory,
+        Pane::Disks,
+        Pane::Pressure,
+        Pane::Processes,
+        Pane::Cgroups,
+        Pane::Filesystems,
+    ];
+
+    /// The name the pane is saved under in the [`State`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Pane::Cpu => "cpu",
+            Pane::Battery => "battery",
+            Pane::Memory => "mem",
+            Pane::Disks => "disks",
+            Pane::Processes => "procs",
+            Pane::Filesystems => "fs",
+            Pane::Pressure => "psi",
+            Pane::Cgroups => "cgroups",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// A frozen copy of the history, displayed while collection continues in the background.
+#[derive(Debug, Clone)]
+pub struct Paused {
+    pub history: History,
+    /// How many samples before the newest one the displayed sample is.
+    pub cursor: usize,
+}
+
+/// Where the snapshots shown by [`App`] come from.
+pub enum Source {
+    /// This system, sampled every tick.
+    Live(Box<Collector>),
+    /// A recording made with `--record`.
+    Replay(Replay),
+    /// An agent started with `--agent`.
+    Remote(RemoteFeed),
+    /// Every agent from the `[hosts]` config section, shown in an overview.
+    Hosts(Hosts),
+}
+
+impl Source {
+    /// How long to wait for the first snapshot from an agent.
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The snapshot the application starts with.
+    fn initial(&mut self) -> AppResult<Snapshot> {
+        Ok(match self {
+            Source::Live(collector) => collector.sample(),
+            Source::Replay(replay) => replay.first().clone(),
+            Source::Remote(feed) => feed.first(Self::CONNECT_TIMEOUT)?,
+            // every host keeps its own history
+            Source::Hosts(_) => Snapshot::default(),
+        })
+    }
+
+    /// Snapshots that became available since the last poll.
+    fn poll(&mut self) -> Vec<Snapshot> {
+        match self {
+            Source::Live(collector) => vec![collector.sample()],
+            Source::Replay(replay) => replay.advance().to_vec(),
+            Source::Remote(feed) => feed.poll(),
+            Source::Hosts(hosts) => {
+                hosts.poll();
+                Vec::new()
+            }
+        }
+    }
+}
+
 /// Application.
 pub struct App {
     /// Is the application running?
     pub running: bool,
     pub input_state: InputState,
+    /// Scroll offset of the help overlay, if it is open.
+    pub help: Option<u16>,
+    /// Where the charts' y axes were drawn last.
+    pub axis_tops: AxisTops,
     pub config: Config,
 
-    pub cpu_history: Vec<VecDeque<f64>>,
-    pub mem_history: VecDeque<f64>,
-    pub mem_total: f64,
-    pub mem_prefix: MemPrefix,
-
-    pub processes: Vec<ProcessInfo>,
-    pub disks: BTreeMap<String, (DiskInfo, VecDeque<DiskInfo>)>,
-
-    pub system: sysinfo::System,
-    pub systemstat: systemstat::System,
+    pub history: History,
+    pub paused: Option<Paused>,
+    pub tiers: Tiers,
+    /// Where the tiers are kept between runs, if they are.
+    store: Option<Store>,
+    /// 0 to show the newest [`HISTORY_LEN`] samples, otherwise the tier of [`TIERS`] before
+    /// this one.
+    pub zoom: usize,
+    /// The history of the tier shown while zoomed out.
+    zoomed: Option<History>,
 
-    last_refresh: Instant,
-    disk_regexes: DiskRegexes,
+    pub source: Source,
+    recorder: Option<Recorder>,
+    exporter: Option<Exporter>,
+    csv_logger: Option<CsvLogger>,
+    pub alerts: Alerts,
+    pub cpu_view: CpuView,
+    pub disk_view: DiskView,
+    pub cgroups: Cgroups,
+    /// The pane the chart options keys apply to.
+    pub focus: Pane,
+    /// The highlighted row of the series list of the focused chart, if it is open.
+    pub series_list: Option<usize>,
+    pub state: State,
+    /// Flips every tick, so that alerted panes flash.
+    pub flash: bool,
 }
 
 impl App {
-    /// Constructs a new instance of [`App`].
-    pub fn new(config: Config) -> Self {
-        let mut system = sysinfo::System::new();
-        let systemstat = systemstat::System::new();
-        system.refresh_cpu();
-        system.refresh_memory();
-        system.refresh_processes();
-        let last_refresh = Instant::now();
-        let disk_regexes = DiskRegexes::default();
-
-        let len = system.cpus().len();
-
-        let disks = systemstat
-            .block_device_statistics()
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|(n, _)| disk_regexes.is_disk(n))
-            .map(|(n, d)| {
-                let q: VecDeque<_> = vec![DiskInfo::default(); HISTORY_LEN].into();
+    /// Constructs a new instance of [`App`], writing every snapshot, the initial one
+    /// included, to `recorder`.
+    pub fn new(
+        config: Config,
+        mut source: Source,
+        mut recorder: Option<Recorder>,
+    ) -> AppResult<Self> {
+        let snapshot = source.initial()?;
 
-                (n, (DiskInfo::new(&d), q))
-            })
-            .collect::<BTreeMap<_, _>>();
+        if let Some(recorder) = &mut recorder {
+            recorder.record(&snapshot)?;
+        }
 
-        let cpu_history = vec![vec![0.0; HISTORY_LEN].into(); len];
+        let mut tiers = Tiers::default();
+        // only this system's history is kept, not that of recordings or other hosts
+        let mut store = match (&source, &config.history) {
+            (Source::Live(_), Some(history)) => Some(Store::open(history, &mut tiers)?),
+            _ => None,
+        };
 
-        let mem_history = vec![0.0; HISTORY_LEN].into();
-        let (mem_total, mem_prefix) = MemPrefix::find_best(system.total_memory() as f64);
+        // hosts keep histories of their own, the initial snapshot of the overview is no sample
+        if !matches!(source, Source::Hosts(_)) {
+            let completed = tiers.push(&snapshot);
+            if let Some(store) = &mut store {
+                store.append(&completed, &tiers)?;
+            }
+        }
 
-        let processes = system
-            .processes()
-            .values()
-            .map(|p| ProcessInfo::new(p, len))
-            .collect();
+        let mut history = History::new(snapshot.cpus.len());
+        history.push(snapshot);
 
-        Self {
+        let csv_logger = config.log_csv.clone().map(CsvLogger::new).transpose()?;
+        let alerts = Alerts::new(config.alerts.clone());
+        let cpu_view = config.cpu_view;
+        let disk_view = config.disk_view;
+
+        Ok(Self {
             running: true,
             input_state: Default::default(),
+            help: None,
+            axis_tops: AxisTops::default(),
             config,
-            cpu_history,
-            mem_history,
-            mem_total,
-            mem_prefix,
-            last_refresh,
-            system,
-            systemstat,
-            processes,
-            disks,
-            disk_regexes: Default::default(),
-        }
+            history,
+            paused: None,
+            tiers,
+            store,
+            zoom: 0,
+            zoomed: None,
+            source,
+            recorder,
+            exporter: None,
+            csv_logger,
+            alerts,
+            cpu_view,
+            disk_view,
+            cgroups: Cgroups::new(),
+            focus: Pane::Cpu,
+            series_list: None,
+            state: State::default(),
+            flash: false,
+        })
+    }
+
+    /// Exposes every snapshot collected from now on on a metrics endpoint.
+    pub fn export(&mut self, exporter: Exporter) {
+        self.exporter = Some(exporter);
     }
 
     /// Handles the tick event of the terminal.
-    pub fn tick(&mut self) {
-        if self.last_refresh.elapsed() >= System::MINIMUM_CPU_UPDATE_INTERVAL {
-            self.system.refresh_cpu();
-            self.last_refresh = Instant::now();
-
-            self.cpu_history
-                .iter_mut()
-                .zip(self.system.cpus())
-                .for_each(|(history, cpu)| {
-                    history.pop_front();
-                    history.push_back(cpu.cpu_usage() as f64)
-                });
-        }
-
-        self.system.refresh_memory();
-        self.mem_history.pop_front();
-        self.mem_history
-            .push_back(self.mem_prefix.convert(self.system.used_memory() as f64));
-
-        self.system.refresh_processes();
-        self.processes = self
-            .system
-            .processes()
-            .values()
-            .map(|p| ProcessInfo::new(p, self.system.cpus().len()))
-            .collect();
-
-        self.systemstat
-            .block_device_statistics()
-            .unwrap_or_default()
+    pub fn tick(&mut self) -> AppResult<()> {
+        self.flash = !self.flash;
+
+        let mut polled = false;
+        for snapshot in self.source.poll() {
+            self.alerts.check(&snapshot);
+
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(&snapshot)?;
+            }
+
+            if let Some(exporter) = &self.exporter {
+                exporter.update(&snapshot);
+            }
+
+            if let Some(csv_logger) = &mut self.csv_logger {
+                csv_logger.log(&snapshot)?;
+            }
+
+            let completed = self.tiers.push(&snapshot);
+            if let Some(store) = &mut self.store {
+                store.append(&completed, &self.tiers)?;
+            }
+            self.update_zoom(&completed);
+
+            self.history.push(snapshot);
+            polled = true;
+        }
+
+        if polled {
+            self.copy_latest_to_zoom();
+        }
+
+        Ok(())
+    }
+
+    /// Zooms the charts `by` tiers out, or in if negative. Paused displays are frozen again
+    /// at the new zoom. Hosts aren't kept in tiers, so they can't be zoomed.
+    pub fn zoom_by(&mut self, by: isize) {
+        if matches!(self.source, Source::Hosts(_)) {
+            return;
+        }
+
+        self.zoom = self.zoom.saturating_add_signed(by).min(TIERS.len());
+        self.refresh_zoom();
+
+        if self.paused.is_some() {
+            self.paused = None;
+            self.toggle_pause();
+        }
+    }
+
+    /// Rebuilds the history of the zoomed out tier from all of its points.
+    fn refresh_zoom(&mut self) {
+        self.zoomed = self
+            .zoom
+            .checked_sub(1)
+            .map(|tier| self.tiers.history(tier));
+        self.copy_latest_to_zoom();
+    }
+
+    /// Brings the zoomed history up to date after a snapshot was pushed into the tiers,
+    /// without rebuilding it. The newest point of the tier is still collecting samples, so
+    /// it is replaced until it completes and the next one is appended.
+    fn update_zoom(&mut self, completed: &[(usize, Point)]) {
+        let (Some(zoomed), Some(tier)) = (&mut self.zoomed, self.zoom.checked_sub(1)) else {
+            return;
+        };
+        let Some(newest) = self.tiers.newest(tier) else {
+            return;
+        };
+
+        let completed = completed
+            .iter()
+            .find(|&&(i, _)| i == tier)
+            .map(|(_, point)| point);
+        let update = |history: &mut History, snapshot: fn(&Point) -> Snapshot| match completed {
+            Some(point) => {
+                history.replace_newest(snapshot(point));
+                history.push(snapshot(newest));
+            }
+            None => history.replace_newest(snapshot(newest)),
+        };
+
+        update(zoomed, Point::average);
+        if let Some([lowest, highest]) = zoomed.bounds.as_deref_mut() {
+            update(lowest, Point::minimum);
+            update(highest, Point::maximum);
+        }
+    }
+
+    /// Tiers only keep what is plotted, so the zoomed history takes everything else from the
+    /// newest sample.
+    fn copy_latest_to_zoom(&mut self) {
+        let Some(zoomed) = &mut self.zoomed else {
+            return;
+        };
+
+        zoomed.governors.clone_from(&self.history.governors);
+        zoomed.load_average = self.history.load_average;
+        zoomed.filesystems.clone_from(&self.history.filesystems);
+        zoomed.power = self.history.power;
+        zoomed.pressure = self.history.pressure;
+        zoomed.cgroups.clone_from(&self.history.cgroups);
+    }
+
+    /// Jumps `by` samples through the replayed recording.
+    pub fn seek(&mut self, by: isize) {
+        let Source::Replay(replay) = &mut self.source else {
+            return;
+        };
+
+        let mut history = History::new(self.history.cpus.len());
+        let mut tiers = Tiers::default();
+        for snapshot in replay.seek(by, HISTORY_LEN) {
+            tiers.push(snapshot);
+            history.push(snapshot.clone());
+        }
+        self.history = history;
+        self.tiers = tiers;
+        self.refresh_zoom();
+    }
+
+    /// Restores the settings saved by a previous run.
+    pub fn restore(&mut self, state: State) {
+        self.state = state;
+    }
+
+    /// Names of the series the chart in the pane can show, in the order they're plotted.
+    pub fn series(&self, pane: Pane) -> Vec<String> {
+        let history = self.displayed_history();
+
+        match pane {
+            Pane::Cpu => match self.cpu_view {
+                CpuView::Usage | CpuView::Frequency => {
+                    let cpus = (0..history.cpus.len()).map(|i| format!("cpu{i}"));
+
+                    if history.cpus.len() > MAX_PLOTTED_CPUS {
+                        std::iter::once("avg".to_string()).chain(cpus).collect()
+                    } else {
+                        cpus.collect()
+                    }
+                }
+                CpuView::Breakdown => CpuTimes::NAMES.iter().map(|n| n.to_string()).collect(),
+                CpuView::Heatmap => Vec::new(),
+            },
+            Pane::Battery => vec!["charge".to_string()],
+            Pane::Memory => vec!["used mem".to_string()],
+            Pane::Disks if self.disk_view == DiskView::Utilization => {
+                history.disks.keys().cloned().collect()
+            }
+            Pane::Disks => history
+                .disks
+                .keys()
+                .flat_map(|disk| [format!("{disk} r"), format!("{disk} w")])
+                .collect(),
+            Pane::Processes | Pane::Filesystems | Pane::Pressure | Pane::Cgroups => Vec::new(),
+        }
+    }
+
+    /// Whether the pane is part of the layout, which the battery pane only is with batteries
+    /// and the cgroups pane only with a cgroup v2 hierarchy.
+    pub fn is_shown(&self, pane: Pane) -> bool {
+        match pane {
+            Pane::Battery => self.displayed_history().power.is_some(),
+            Pane::Cgroups => !self.displayed_history().cgroups.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Focuses the next shown pane, or the previous one if not `forward`.
+    pub fn cycle_focus(&mut self, forward: bool) {
+        loop {
+            self.focus = if forward {
+                self.focus.next()
+            } else {
+                self.focus.previous()
+            };
+
+            if self.is_shown(self.focus) {
+                break;
+            }
+        }
+    }
+
+    pub fn is_hidden(&self, pane: Pane, series: &str) -> bool {
+        self.state
+            .hidden_series
+            .get(pane.name())
+            .is_some_and(|hidden| hidden.contains(series))
+    }
+
+    /// Opens the series list of the focused chart, if it has any series.
+    pub fn open_series_list(&mut self) {
+        if !self.series(self.focus).is_empty() {
+            self.series_list = Some(0);
+        }
+    }
+
+    /// Moves the highlight of the series list by `by` rows.
+    pub fn select_series(&mut self, by: isize) {
+        let len = self.series(self.focus).len();
+
+        if let Some(selected) = &mut self.series_list {
+            *selected = selected
+                .saturating_add_signed(by)
+                .min(len.saturating_sub(1));
+        }
+    }
+
+    /// The series highlighted in the series list.
+    fn selected_series(&self) -> Option<String> {
+        self.series(self.focus).get(self.series_list?).cloned()
+    }
+
+    /// Shows or hides the highlighted series.
+    pub fn toggle_series(&mut self) {
+        let Some(series) = self.selected_series() else {
+            return;
+        };
+
+        let hidden = self
+            .state
+            .hidden_series
+            .entry(self.focus.name().to_string())
+            .or_default();
+        if !hidden.remove(&series) {
+            hidden.insert(series);
+        }
+    }
+
+    /// Hides every series but the highlighted one, or shows every series if that's the
+    /// case already.
+    pub fn solo_series(&mut self) {
+        let Some(series) = self.selected_series() else {
+            return;
+        };
+
+        let others = self
+            .series(self.focus)
             .into_iter()
-            .filter(|(n, _)| self.disk_regexes.is_disk(n))
-            .map(|(n, d)| (n, DiskInfo::new(&d)))
-            .for_each(|(name, current)| {
-                let (prev, history) = self.disks.entry(name).or_insert((
-                    Default::default(),
-                    vec![Default::default(); HISTORY_LEN].into(),
-                ));
-                history.pop_front();
-                history.push_back(DiskInfo {
-                    r_sectors: current.r_sectors - prev.r_sectors,
-                    w_sectors: current.w_sectors - prev.w_sectors,
-                });
-                *prev = current;
-            });
+            .filter(|s| *s != series)
+            .collect::<BTreeSet<_>>();
+
+        let hidden = self
+            .state
+            .hidden_series
+            .entry(self.focus.name().to_string())
+            .or_default();
+        if *hidden == others {
+            hidden.clear();
+        } else {
+            *hidden = others;
+        }
+    }
+
+    /// The options of the focused chart, `None` if the focused pane has no chart.
+    pub fn focused_chart_mut(&mut self) -> Option<&mut ChartOptions> {
+        self.config.charts.get_mut(self.focus)
+    }
+
+    pub fn replay_mut(&mut self) -> Option<&mut Replay> {
+        match &mut self.source {
+            Source::Replay(replay) => Some(replay),
+            Source::Live(_) | Source::Remote(_) | Source::Hosts(_) => None,
+        }
+    }
+
+    pub fn hosts_mut(&mut self) -> Option<&mut Hosts> {
+        match &mut self.source {
+            Source::Hosts(hosts) => Some(hosts),
+            _ => None,
+        }
+    }
+
+    /// Whether the hosts overview is shown instead of the usual layout.
+    pub fn in_overview(&self) -> bool {
+        matches!(&self.source, Source::Hosts(hosts) if hosts.opened.is_none())
+    }
+
+    /// Shows the host highlighted in the overview in the usual layout.
+    pub fn open_host(&mut self) {
+        self.paused = None;
+        if let Some(hosts) = self.hosts_mut() {
+            if !hosts.hosts.is_empty() {
+                hosts.opened = Some(hosts.selected);
+            }
+        }
+    }
+
+    /// Goes back from a single host to the overview.
+    pub fn close_host(&mut self) {
+        self.paused = None;
+        if let Some(hosts) = self.hosts_mut() {
+            hosts.opened = None;
+        }
+    }
+
+    /// The history collected from the shown system, kept up to date even while paused.
+    pub fn live_history(&self) -> &History {
+        match &self.source {
+            Source::Hosts(hosts) => hosts.opened().map_or(&self.history, |h| &h.history),
+            _ => self.zoomed.as_ref().unwrap_or(&self.history),
+        }
+    }
+
+    /// The history shown on screen, which is frozen while paused.
+    pub fn displayed_history(&self) -> &History {
+        match &self.paused {
+            Some(paused) => &paused.history,
+            None => self.live_history(),
+        }
+    }
+
+    /// How many samples before the newest one the displayed sample is, if paused.
+    pub fn cursor(&self) -> Option<usize> {
+        self.paused.as_ref().map(|p| p.cursor)
+    }
+
+    /// Processes as they were at the displayed sample, or the newest ones while zoomed out
+    /// as tiers don't keep processes.
+    pub fn displayed_processes(&self) -> &[ProcessInfo] {
+        let history = self.displayed_history();
+        if history.bounds.is_some() {
+            return self.history.processes.back().map_or(&[], Vec::as_slice);
+        }
+
+        &history.processes[history.index(self.cursor().unwrap_or_default())]
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = match self.paused {
+            Some(_) => None,
+            None => Some(Paused {
+                history: self.live_history().clone(),
+                cursor: 0,
+            }),
+        };
+    }
+
+    /// Moves the cursor of a paused display by `by` samples back in time.
+    pub fn scrub(&mut self, by: isize) {
+        if let Some(paused) = &mut self.paused {
+            let oldest = paused.history.collected().saturating_sub(1);
+            paused.cursor = paused.cursor.saturating_add_signed(by).min(oldest);
+        }
+    }
+
+    /// The key bindings scope for the current input.
+    pub fn scope(&self) -> Scope {
+        if self.help.is_some() {
+            Scope::Help
+        } else if self.series_list.is_some() {
+            Scope::Series
+        } else {
+            self.mode()
+        }
+    }
+
+    /// The key bindings scope shown under the help overlay.
+    pub fn mode(&self) -> Scope {
+        if self.in_overview() {
+            Scope::Overview
+        } else {
+            match self.input_state.scope() {
+                Scope::Normal if self.focus == Pane::Cgroups => Scope::Cgroups,
+                scope => scope,
+            }
+        }
     }
 
     /// Set running to false to quit the application.
@@ -291,3 +955,148 @@ impl App {
         self.running = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(secs: u64) -> Snapshot {
+        Snapshot {
+            timestamp: Duration::from_secs(secs),
+            cpus: vec![secs as f64 % 100.0, 100.0 - secs as f64 % 100.0],
+            mem_used: secs * 1024,
+            mem_total: 1 << 30,
+            ..Default::default()
+        }
+    }
+
+    /// A history of `len` samples taken a second apart.
+    fn history(len: u64) -> History {
+        let mut history = History::new(2);
+        for secs in 1..=len {
+            history.push(snapshot(secs));
+        }
+
+        history
+    }
+
+    /// An app replaying a recording of `len` samples taken a second apart.
+    fn replay(len: u64) -> App {
+        let replay = Replay::new((0..len).map(snapshot).collect());
+        App::new(Config::default(), Source::Replay(replay), None).unwrap()
+    }
+
+    #[test]
+    fn scrub_while_paused() {
+        let mut app = App::new(Config::default(), Source::Live(Box::default()), None).unwrap();
+        app.history = history(6);
+
+        app.toggle_pause();
+        assert_eq!(app.cursor(), Some(0));
+
+        // the cursor stays within the samples collected when pausing
+        app.scrub(3);
+        assert_eq!(app.cursor(), Some(3));
+        app.scrub(100);
+        assert_eq!(app.cursor(), Some(5));
+        app.scrub(-100);
+        assert_eq!(app.cursor(), Some(0));
+
+        // collection goes on in the background without moving the display
+        app.history.push(snapshot(7));
+        assert_eq!(app.displayed_history().collected(), 6);
+        assert_eq!(app.history.collected(), 7);
+
+        app.toggle_pause();
+        assert_eq!(app.cursor(), None);
+        assert_eq!(app.displayed_history().collected(), 7);
+
+        // scrubbing does nothing unless paused
+        app.scrub(1);
+        assert_eq!(app.cursor(), None);
+    }
+
+    #[test]
+    fn solo_and_hide_series() {
+        let mut app = App::new(Config::default(), Source::Live(Box::default()), None).unwrap();
+        app.history = history(10);
+        app.focus = Pane::Cpu;
+        app.open_series_list();
+        assert_eq!(app.series(Pane::Cpu), ["cpu0", "cpu1"]);
+
+        app.select_series(1);
+        app.solo_series();
+        assert!(app.is_hidden(Pane::Cpu, "cpu0"));
+        assert!(!app.is_hidden(Pane::Cpu, "cpu1"));
+
+        // soloing the only shown series again shows all of them
+        app.solo_series();
+        assert!(!app.is_hidden(Pane::Cpu, "cpu0"));
+        assert!(!app.is_hidden(Pane::Cpu, "cpu1"));
+
+        // the last shown series can be hidden too, and shown again
+        app.toggle_series();
+        app.select_series(-1);
+        app.toggle_series();
+        assert!(app.is_hidden(Pane::Cpu, "cpu0"));
+        assert!(app.is_hidden(Pane::Cpu, "cpu1"));
+
+        app.toggle_series();
+        assert!(!app.is_hidden(Pane::Cpu, "cpu0"));
+        assert!(app.is_hidden(Pane::Cpu, "cpu1"));
+    }
+
+    #[test]
+    fn hosts_are_not_zoomed() {
+        let mut app =
+            App::new(Config::default(), Source::Hosts(Hosts::connect(&[])), None).unwrap();
+        assert!(app.tiers.newest(0).is_none());
+
+        app.zoom_by(1);
+        assert_eq!(app.zoom, 0);
+        assert!(app.live_history().bounds.is_none());
+    }
+
+    #[test]
+    fn records_the_initial_snapshot() {
+        let path = std::env::temp_dir().join(format!("jwtop-record-{}", std::process::id()));
+        let replay = Replay::new((0..3).map(snapshot).collect());
+        let recorder = Recorder::create(&path).unwrap();
+        App::new(Config::default(), Source::Replay(replay), Some(recorder)).unwrap();
+
+        let recorded = Replay::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recorded.len(), 1);
+    }
+
+    #[test]
+    fn zoom_follows_tiers() {
+        let mut app = replay(100);
+        app.zoom_by(2);
+
+        // whole tier points complete during some ticks and not during others
+        for speed in [1.0, 4.0, 16.0, 8.0, 2.0] {
+            app.replay_mut().unwrap().speed = speed;
+            app.tick().unwrap();
+
+            let rebuilt = app.tiers.history(1);
+            let zoomed = app.live_history();
+            assert_eq!(zoomed.timestamps, rebuilt.timestamps);
+            assert_eq!(zoomed.cpus, rebuilt.cpus);
+            assert_eq!(zoomed.mem, rebuilt.mem);
+
+            let [lowest, highest] = zoomed.bounds.as_deref().unwrap();
+            let [rebuilt_lowest, rebuilt_highest] = rebuilt.bounds.as_deref().unwrap();
+            assert_eq!(lowest.cpus, rebuilt_lowest.cpus);
+            assert_eq!(highest.cpus, rebuilt_highest.cpus);
+            assert!(lowest.cpus[0]
+                .iter()
+                .zip(&zoomed.cpus[0])
+                .all(|(min, avg)| min <= avg));
+            assert!(highest.cpus[0]
+                .iter()
+                .zip(&zoomed.cpus[0])
+                .all(|(max, avg)| max >= avg));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use crate::{collector::CgroupInfo, ui::processes::SortDirection};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CgroupColumn {
+    Name,
+    #[default]
+    Cpu,
+    Memory,
+    IoRead,
+    IoWrite,
+    Pids,
+}
+
+impl CgroupColumn {
+    pub fn default_sort_direction(&self) -> SortDirection {
+        match self {
+            CgroupColumn::Name => SortDirection::Ascending,
+            _ => SortDirection::Descending,
+        }
+    }
+
+    fn compare_by(&self, g1: &CgroupInfo, g2: &CgroupInfo) -> Ordering {
+        match self {
+            CgroupColumn::Name => g1.name().cmp(g2.name()),
+            CgroupColumn::Cpu => g1.cpu.total_cmp(&g2.cpu),
+            CgroupColumn::Memory => g1.mem.cmp(&g2.mem),
+            CgroupColumn::IoRead => g1.io_r.cmp(&g2.io_r),
+            CgroupColumn::IoWrite => g1.io_w.cmp(&g2.io_w),
+            CgroupColumn::Pids => g1.pids.cmp(&g2.pids),
+        }
+    }
+}
+
+/// A line of the cgroup tree.
+pub struct CgroupRow<'a> {
+    pub info: &'a CgroupInfo,
+    pub depth: usize,
+    pub has_children: bool,
+    pub collapsed: bool,
+}
+
+/// How the cgroup tree is sorted, which group is highlighted and which are collapsed.
+#[derive(Debug)]
+pub struct Cgroups {
+    pub column: CgroupColumn,
+    pub direction: SortDirection,
+    /// Path of the highlighted group, the first one if `None`.
+    pub selected: Option<String>,
+    /// Paths of the groups whose children are hidden.
+    pub collapsed: BTreeSet<String>,
+}
+
+impl Default for Cgroups {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cgroups {
+    pub fn new() -> Self {
+        let column = CgroupColumn::default();
+
+        Self {
+            column,
+            direction: column.default_sort_direction(),
+            selected: None,
+            collapsed: BTreeSet::new(),
+        }
+    }
+
+    /// The groups in the order they're shown, every group after its parent and siblings
+    /// sorted among themselves. Children of collapsed groups are left out.
+    pub fn rows<'a>(&self, groups: &'a [CgroupInfo]) -> Vec<CgroupRow<'a>> {
+        let paths = groups
+            .iter()
+            .map(|g| g.path.as_str())
+            .collect::<BTreeSet<_>>();
+
+        let mut children = BTreeMap::<Option<&str>, Vec<&CgroupInfo>>::new();
+        for group in groups {
+            // groups whose parent vanished between reads are shown at the top
+            let parent = group.parent().filter(|parent| paths.contains(parent));
+            children.entry(parent).or_default().push(group);
+        }
+
+        for siblings in children.values_mut() {
+            siblings.sort_by(|g1, g2| {
+                let ordering = self.column.compare_by(g1, g2);
+
+                match self.direction {
+                    SortDirection::Ascending => ordering,
+                    SortDirection::Descending => ordering.reverse(),
+                }
+            });
+        }
+
+        let mut rows = Vec::with_capacity(groups.len());
+        let mut pending = children
+            .get(&None)
+            .map(|roots| roots.iter().rev().map(|&g| (g, 0)).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        while let Some((info, depth)) = pending.pop() {
+            let below = children.get(&Some(info.path.as_str()));
+            let collapsed = self.collapsed.contains(&info.path);
+
+            rows.push(CgroupRow {
+                info,
+                depth,
+                has_children: below.is_some(),
+                collapsed,
+            });
+
+            if let (Some(below), false) = (below, collapsed) {
+                pending.extend(below.iter().rev().map(|&g| (g, depth + 1)));
+            }
+        }
+
+        rows
+    }
+
+    /// Index into [`Cgroups::rows`] of the highlighted group.
+    pub fn selected_row(&self, rows: &[CgroupRow]) -> usize {
+        self.selected
+            .as_ref()
+            .and_then(|selected| rows.iter().position(|r| &r.info.path == selected))
+            .unwrap_or_default()
+    }
+
+    pub fn sort_by(&mut self, column: CgroupColumn) {
+        if self.column == column {
+            self.direction = self.direction.reversed();
+        } else {
+            self.column = column;
+            self.direction = column.default_sort_direction();
+        }
+    }
+
+    /// Moves the highlight `by` rows down, or up if negative.
+    pub fn select(&mut self, groups: &[CgroupInfo], by: isize) {
+        let rows = self.rows(groups);
+        if rows.is_empty() {
+            return;
+        }
+
+        let position = self
+            .selected_row(&rows)
+            .saturating_add_signed(by)
+            .min(rows.len() - 1);
+        self.selected = Some(rows[position].info.path.clone());
+    }
+
+    /// Collapses the highlighted group, or expands it if it is collapsed.
+    pub fn toggle(&mut self, groups: &[CgroupInfo]) {
+        let rows = self.rows(groups);
+        let Some(row) = rows.get(self.selected_row(&rows)) else {
+            return;
+        };
+
+        if row.has_children && !self.collapsed.remove(&row.info.path) {
+            self.collapsed.insert(row.info.path.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(path: &str, cpu: f64) -> CgroupInfo {
+        CgroupInfo {
+            path: path.into(),
+            cpu,
+            ..Default::default()
+        }
+    }
+
+    fn paths(cgroups: &Cgroups, groups: &[CgroupInfo]) -> Vec<String> {
+        cgroups
+            .rows(groups)
+            .iter()
+            .map(|r| format!("{}{}", " ".repeat(r.depth), r.info.name()))
+            .collect()
+    }
+
+    #[test]
+    fn tree() {
+        let groups = [
+            group("/", 0.0),
+            group("/a", 10.0),
+            group("/a/x", 1.0),
+            group("/a/y", 9.0),
+            group("/b", 20.0),
+        ];
+        let mut cgroups = Cgroups::new();
+
+        assert_eq!(paths(&cgroups, &groups), ["/", " b", " a", "  y", "  x"]);
+
+        cgroups.sort_by(CgroupColumn::Name);
+        assert_eq!(paths(&cgroups, &groups), ["/", " a", "  x", "  y", " b"]);
+
+        cgroups.select(&groups, 1);
+        cgroups.toggle(&groups);
+        assert_eq!(paths(&cgroups, &groups), ["/", " a", " b"]);
+
+        cgroups.select(&groups, 5);
+        assert_eq!(cgroups.selected.as_deref(), Some("/b"));
+
+        // leaves can't be collapsed
+        cgroups.toggle(&groups);
+        assert!(!cgroups.collapsed.contains("/b"));
+    }
+}
@@ -0,0 +1,523 @@
+mod cgroups;
+mod cpu_times;
+mod diskstats;
+mod filesystems;
+mod power;
+mod pressure;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{CpuExt, CpuRefreshKind, PidExt, Process, ProcessExt, System, SystemExt};
+use systemstat::Platform;
+
+pub use cgroups::{CgroupInfo, DEFAULT_CGROUP_ROOT};
+pub use cpu_times::CpuTimes;
+pub use filesystems::FilesystemInfo;
+pub use power::{ChargeState, PowerInfo, DEFAULT_POWER_SUPPLY_ROOT};
+pub use pressure::{Pressure, PressureStats, ResourcePressure};
+
+use cgroups::CgroupCounters;
+use cpu_times::CpuTicks;
+use diskstats::DiskCounters;
+
+// FIXME: some disks have sector size != 512
+pub const SECTOR_SIZE: usize = 512;
+
+pub const DEFAULT_CPUFREQ_ROOT: &str = "/sys/devices/system/cpu";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub cpu: f64,
+    pub mem: u64,
+    pub name: String,
+    pub disk_r: u64,
+    pub disk_w: u64,
+}
+
+impl ProcessInfo {
+    fn new(proc: &Process, cpu_amount: usize) -> Self {
+        Self {
+            pid: proc.pid().as_u32(),
+            cpu: proc.cpu_usage() as f64 / cpu_amount as f64,
+            mem: proc.memory(),
+            name: proc.name().to_string(),
+            // FIXME: as per documentation, this is incorrect for FreeBSD and Windows
+            disk_r: proc.disk_usage().read_bytes,
+            disk_w: proc.disk_usage().written_bytes,
+        }
+    }
+}
+
+/// What a disk did since the previous snapshot.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub r_sectors: usize,
+    pub w_sectors: usize,
+    /// Reads and writes completed.
+    pub reads: u64,
+    pub writes: u64,
+    /// Milliseconds the completed reads and writes took, summed up.
+    pub read_ms: u64,
+    pub write_ms: u64,
+    /// Requests in flight at the time of the snapshot.
+    pub in_flight: u64,
+    /// Milliseconds the disk was busy.
+    pub io_ms: u64,
+    /// Milliseconds requests spent queued or in service, summed up.
+    pub weighted_ms: u64,
+    /// Milliseconds since the previous snapshot.
+    pub elapsed_ms: u64,
+}
+
+impl DiskInfo {
+    pub const FIELDS: usize = 10;
+
+    /// Every field as a float, to be aggregated. Undone by [`DiskInfo::from_values`].
+    pub fn values(&self) -> [f64; Self::FIELDS] {
+        [
+            self.r_sectors as f64,
+            self.w_sectors as f64,
+            self.reads as f64,
+            self.writes as f64,
+            self.read_ms as f64,
+            self.write_ms as f64,
+            self.in_flight as f64,
+            self.io_ms as f64,
+            self.weighted_ms as f64,
+            self.elapsed_ms as f64,
+        ]
+    }
+
+    pub fn from_values(values: [f64; Self::FIELDS]) -> Self {
+        let [r_sectors, w_sectors, reads, writes, read_ms, write_ms, in_flight, io_ms, weighted_ms, elapsed_ms] =
+            values.map(|v| v.round() as u64);
+
+        Self {
+            r_sectors: r_sectors as usize,
+            w_sectors: w_sectors as usize,
+            reads,
+            writes,
+            read_ms,
+            write_ms,
+            in_flight,
+            io_ms,
+            weighted_ms,
+            elapsed_ms,
+        }
+    }
+
+    fn elapsed_secs(&self) -> f64 {
+        self.elapsed_ms as f64 / 1000.0
+    }
+
+    /// Reads and writes completed per second.
+    pub fn iops(&self) -> [f64; 2] {
+        if self.elapsed_ms == 0 {
+            return [0.0; 2];
+        }
+
+        [
+            self.reads as f64 / self.elapsed_secs(),
+            self.writes as f64 / self.elapsed_secs(),
+        ]
+    }
+
+    /// Bytes read and written per second.
+    pub fn bytes_per_second(&self) -> [f64; 2] {
+        if self.elapsed_ms == 0 {
+            return [0.0; 2];
+        }
+
+        [
+            (self.r_sectors * SECTOR_SIZE) as f64 / self.elapsed_secs(),
+            (self.w_sectors * SECTOR_SIZE) as f64 / self.elapsed_secs(),
+        ]
+    }
+
+    /// Average milliseconds a read and a write took.
+    pub fn await_ms(&self) -> [f64; 2] {
+        let average = |ms: u64, requests: u64| {
+            if requests == 0 {
+                0.0
+            } else {
+                ms as f64 / requests as f64
+            }
+        };
+
+        [
+            average(self.read_ms, self.reads),
+            average(self.write_ms, self.writes),
+        ]
+    }
+
+    /// Share of the time the disk was busy, in percent.
+    pub fn utilization(&self) -> f64 {
+        if self.elapsed_ms == 0 {
+            return 0.0;
+        }
+
+        (self.io_ms as f64 / self.elapsed_ms as f64 * 100.0).min(100.0)
+    }
+
+    /// Average amount of requests queued or in service.
+    pub fn queue_depth(&self) -> f64 {
+        if self.elapsed_ms == 0 {
+            return 0.0;
+        }
+
+        self.weighted_ms as f64 / self.elapsed_ms as f64
+    }
+}
+
+struct DiskRegexes {
+    disks: Vec<Regex>,
+}
+
+impl DiskRegexes {
+    fn is_disk(&self, name: &str) -> bool {
+        self.disks.iter().any(|r| r.is_match(name))
+    }
+}
+
+impl Default for DiskRegexes {
+    fn default() -> Self {
+        Self {
+            disks: vec![
+                Regex::new(r"^nvme[0-9]+n[0-9]+$").unwrap(),
+                Regex::new(r"^sd[a-z]+$").unwrap(),
+                Regex::new(r"^hd[a-z]+$").unwrap(),
+                Regex::new(r"^disk[0-9]+$").unwrap(),
+            ],
+        }
+    }
+}
+
+/// Everything jwtop monitors, sampled at a single point in time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Time since the unix epoch at which the snapshot was taken.
+    pub timestamp: Duration,
+    /// Usage of every cpu in percent.
+    pub cpus: Vec<f64>,
+    /// Current frequency of every cpu in MHz.
+    pub frequencies: Vec<u64>,
+    /// Scaling governor of every cpu, empty if it isn't known.
+    pub governors: Vec<String>,
+    /// How every cpu spent its time since the previous snapshot, empty where `/proc/stat`
+    /// isn't available.
+    pub cpu_times: Vec<CpuTimes>,
+    /// How all cpus together spent their time since the previous snapshot.
+    pub total_cpu_times: CpuTimes,
+    pub mem_used: u64,
+    pub mem_total: u64,
+    /// Load averaged over 1, 5 and 15 minutes.
+    pub load_average: [f64; 3],
+    /// Sectors read and written by every disk since the previous snapshot.
+    pub disks: BTreeMap<String, DiskInfo>,
+    pub filesystems: Vec<FilesystemInfo>,
+    /// The batteries, `None` if there are none.
+    pub power: Option<PowerInfo>,
+    /// Pressure stall information, `None` if the kernel has none.
+    pub pressure: Option<Pressure>,
+    /// Every group of the cgroup v2 hierarchy, empty without one.
+    pub cgroups: Vec<CgroupInfo>,
+    pub processes: Vec<ProcessInfo>,
+}
+
+/// Samples the local system.
+pub struct Collector {
+    system: sysinfo::System,
+    systemstat: systemstat::System,
+
+    last_refresh: Instant,
+    cpus: Vec<f64>,
+    /// `/proc/diskstats` counters as of the previous snapshot, and when they were read.
+    disks: BTreeMap<String, DiskCounters>,
+    disks_read: Instant,
+    disk_regexes: DiskRegexes,
+    /// The sysfs directory containing a `cpuN/cpufreq` directory for every cpu.
+    cpufreq_root: PathBuf,
+    /// The sysfs directory containing a directory for every battery and mains supply.
+    power_supply_root: PathBuf,
+    /// The root of the cgroup v2 hierarchy.
+    cgroup_root: PathBuf,
+    /// Counters of every cgroup by path as of the previous snapshot, and when they were read.
+    cgroups: BTreeMap<String, CgroupCounters>,
+    cgroups_read: Instant,
+    /// `/proc/stat` counters as of the previous snapshot, the aggregate of all cpus first.
+    cpu_ticks: Vec<CpuTicks>,
+    /// Types of the filesystems that aren't read.
+    exclude_filesystem_types: Vec<String>,
+    /// Started by the first snapshot, so that it reads the filesystems the builder asked for.
+    filesystems: Option<filesystems::Sampler>,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        let mut system = sysinfo::System::new();
+        let systemstat = systemstat::System::new();
+        system.refresh_cpu();
+        system.refresh_memory();
+        system.refresh_processes();
+        let last_refresh = Instant::now();
+        let disk_regexes = DiskRegexes::default();
+
+        let cpus = vec![0.0; system.cpus().len()];
+
+        Self {
+            system,
+            systemstat,
+            last_refresh,
+            cpus,
+            disks: read_disk_counters(),
+            disks_read: Instant::now(),
+            disk_regexes,
+            cpufreq_root: DEFAULT_CPUFREQ_ROOT.into(),
+            power_supply_root: DEFAULT_POWER_SUPPLY_ROOT.into(),
+            cgroup_root: DEFAULT_CGROUP_ROOT.into(),
+            cgroups: BTreeMap::new(),
+            cgroups_read: Instant::now(),
+            cpu_ticks: read_cpu_ticks(),
+            exclude_filesystem_types: Vec::new(),
+            filesystems: None,
+        }
+    }
+
+    /// Reads cpu frequencies and governors from `root` instead of [`DEFAULT_CPUFREQ_ROOT`].
+    pub fn cpufreq_root(self, root: PathBuf) -> Self {
+        Self {
+            cpufreq_root: root,
+            ..self
+        }
+    }
+
+    /// Reads batteries from `root` instead of [`DEFAULT_POWER_SUPPLY_ROOT`].
+    pub fn power_supply_root(self, root: PathBuf) -> Self {
+        Self {
+            power_supply_root: root,
+            ..self
+        }
+    }
+
+    /// Reads cgroups from `root` instead of [`DEFAULT_CGROUP_ROOT`].
+    pub fn cgroup_root(self, root: PathBuf) -> Self {
+        Self {
+            cgroup_root: root,
+            ..self
+        }
+    }
+
+    /// Leaves filesystems of these types out without reading them.
+    pub fn exclude_filesystem_types(self, types: Vec<String>) -> Self {
+        Self {
+            exclude_filesystem_types: types,
+            ..self
+        }
+    }
+
+    fn read_cpufreq(&self, cpu: usize, file: &str) -> Option<String> {
+        let path = self
+            .cpufreq_root
+            .join(format!("cpu{cpu}"))
+            .join("cpufreq")
+            .join(file);
+
+        fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    }
+
+    /// Frequencies from sysfs, falling back to what sysinfo reports where sysfs has none.
+    fn frequencies(&mut self) -> Vec<u64> {
+        self.system
+            .refresh_cpu_specifics(CpuRefreshKind::new().with_frequency());
+
+        let reported = self
+            .system
+            .cpus()
+            .iter()
+            .map(|cpu| cpu.frequency())
+            .collect::<Vec<_>>();
+        self.sysfs_frequencies(&reported)
+    }
+
+    /// The frequency of every cpu in MHz from sysfs, or the `reported` one where sysfs has
+    /// none.
+    fn sysfs_frequencies(&self, reported: &[u64]) -> Vec<u64> {
+        reported
+            .iter()
+            .enumerate()
+            .map(|(i, &mhz)| {
+                self.read_cpufreq(i, "scaling_cur_freq")
+                    .and_then(|khz| khz.parse::<u64>().ok())
+                    .map_or(mhz, |khz| khz / 1000)
+            })
+            .collect()
+    }
+
+    /// The scaling governor of each of the first `cpus` cpus, empty where there is none.
+    fn governors(&self, cpus: usize) -> Vec<String> {
+        (0..cpus)
+            .map(|i| self.read_cpufreq(i, "scaling_governor").unwrap_or_default())
+            .collect()
+    }
+
+    /// Takes a new snapshot. Disk usage is reported relative to the previous call.
+    pub fn sample(&mut self) -> Snapshot {
+        if self.last_refresh.elapsed() >= System::MINIMUM_CPU_UPDATE_INTERVAL {
+            self.system.refresh_cpu();
+            self.last_refresh = Instant::now();
+
+            self.cpus = self
+                .system
+                .cpus()
+                .iter()
+                .map(|cpu| cpu.cpu_usage() as f64)
+                .collect();
+        }
+
+        self.system.refresh_memory();
+
+        self.system.refresh_processes();
+        let processes = self
+            .system
+            .processes()
+            .values()
+            .map(|p| ProcessInfo::new(p, self.system.cpus().len()))
+            .collect();
+
+        let disk_counters = read_disk_counters();
+        let elapsed = self.disks_read.elapsed();
+        self.disks_read = Instant::now();
+        let disks = disk_counters
+            .iter()
+            .filter(|(name, _)| self.disk_regexes.is_disk(name))
+            .map(|(name, counters)| {
+                let previous = self.disks.get(name).copied().unwrap_or_default();
+                (name.clone(), counters.since(&previous, elapsed))
+            })
+            .collect();
+        self.disks = disk_counters;
+
+        let cgroups = cgroups::read_cgroups(&self.cgroup_root);
+        let elapsed = self.cgroups_read.elapsed();
+        self.cgroups_read = Instant::now();
+        let cgroup_infos = cgroups
+            .iter()
+            .map(|(path, cgroup)| {
+                // groups created since the previous snapshot start out idle
+                let previous = self.cgroups.get(path).unwrap_or(&cgroup.counters);
+                cgroup.since(path, previous, elapsed)
+            })
+            .collect();
+        self.cgroups = cgroups
+            .into_iter()
+            .map(|(path, cgroup)| (path, cgroup.counters))
+            .collect();
+
+        let cpu_ticks = read_cpu_ticks();
+        let mut cpu_times = cpu_ticks
+            .iter()
+            .zip(&self.cpu_ticks)
+            .map(|(now, before)| now.since(before))
+            .collect::<Vec<_>>();
+        self.cpu_ticks = cpu_ticks;
+        let total_cpu_times = if cpu_times.is_empty() {
+            CpuTimes::default()
+        } else {
+            cpu_times.remove(0)
+        };
+
+        let frequencies = self.frequencies();
+        let governors = self.governors(frequencies.len());
+
+        Snapshot {
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+            cpus: self.cpus.clone(),
+            frequencies,
+            governors,
+            cpu_times,
+            total_cpu_times,
+            mem_used: self.system.used_memory(),
+            mem_total: self.system.total_memory(),
+            load_average: {
+                let load = self.system.load_average();
+                [load.one, load.five, load.fifteen]
+            },
+            disks,
+            filesystems: self
+                .filesystems
+                .get_or_insert_with(|| {
+                    filesystems::Sampler::spawn(self.exclude_filesystem_types.clone())
+                })
+                .latest()
+                .to_vec(),
+            power: power::read(&self.power_supply_root, &self.systemstat),
+            pressure: pressure::read_pressure(Path::new(pressure::PROC_PRESSURE)),
+            cgroups: cgroup_infos,
+            processes,
+        }
+    }
+}
+
+fn read_disk_counters() -> BTreeMap<String, DiskCounters> {
+    fs::read_to_string(diskstats::PROC_DISKSTATS)
+        .map(|stats| diskstats::parse_diskstats(&stats))
+        .unwrap_or_default()
+}
+
+fn read_cpu_ticks() -> Vec<CpuTicks> {
+    fs::read_to_string(cpu_times::PROC_STAT)
+        .map(|stat| cpu_times::parse_proc_stat(&stat))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpufreq() {
+        let root = std::env::temp_dir().join(format!("jwtop-cpufreq-{}", std::process::id()));
+        let write = |cpu: usize, file: &str, contents: &str| {
+            let dir = root.join(format!("cpu{cpu}")).join("cpufreq");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(file), format!("{contents}\n")).unwrap();
+        };
+
+        write(0, "scaling_cur_freq", "2400000");
+        write(0, "scaling_governor", "performance");
+        // no governor
+        write(1, "scaling_cur_freq", "800000");
+        // no current frequency, or an unreadable one
+        write(2, "scaling_governor", "powersave");
+        write(3, "scaling_cur_freq", "fast");
+        // cpu4 has no cpufreq directory at all
+
+        let collector = Collector::new().cpufreq_root(root.clone());
+        assert_eq!(
+            collector.sysfs_frequencies(&[1000, 1100, 1200, 1300, 1400]),
+            [2400, 800, 1200, 1300, 1400]
+        );
+        assert_eq!(
+            collector.governors(5),
+            ["performance", "", "powersave", "", ""]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// What a cgroup of the v2 hierarchy and everything below it used since the previous
+/// snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CgroupInfo {
+    /// Path below the root of the hierarchy, `/` for the root itself.
+    pub path: String,
+    /// Cpu time used, in percent of a single cpu.
+    pub cpu: f64,
+    /// `memory.current` in bytes.
+    pub mem: u64,
+    /// `memory.max` in bytes, `None` if unlimited.
+    pub mem_max: Option<u64>,
+    /// Bytes read per second from all devices.
+    pub io_r: u64,
+    /// Bytes written per second to all devices.
+    pub io_w: u64,
+    pub pids: u64,
+}
+
+impl CgroupInfo {
+    /// The last component of the path.
+    pub fn name(&self) -> &str {
+        match self.path.rsplit_once('/') {
+            Some((_, "")) | None => "/",
+            Some((_, name)) => name,
+        }
+    }
+
+    /// Path of the group this one is in, `None` for the root.
+    pub fn parent(&self) -> Option<&str> {
+        match self.path.rsplit_once('/')? {
+            (_, "") => None,
+            ("", _) => Some("/"),
+            (parent, _) => Some(parent),
+        }
+    }
+}
+
+/// Counters of a single cgroup, since it was created.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CgroupCounters {
+    usage_usec: u64,
+    rbytes: u64,
+    wbytes: u64,
+}
+
+/// The files of a single cgroup directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cgroup {
+    pub counters: CgroupCounters,
+    mem: u64,
+    mem_max: Option<u64>,
+    pids: u64,
+}
+
+impl Cgroup {
+    fn read(dir: &Path) -> Self {
+        let read = |file: &str| fs::read_to_string(dir.join(file)).unwrap_or_default();
+        let number = |file: &str| read(file).trim().parse().unwrap_or_default();
+        let (rbytes, wbytes) = parse_io_stat(&read("io.stat"));
+
+        Self {
+            counters: CgroupCounters {
+                usage_usec: parse_usage_usec(&read("cpu.stat")),
+                rbytes,
+                wbytes,
+            },
+            mem: number("memory.current"),
+            mem_max: parse_max(&read("memory.max")),
+            pids: number("pids.current"),
+        }
+    }
+
+    /// What the group used since `previous`, read `elapsed` earlier.
+    pub fn since(&self, path: &str, previous: &CgroupCounters, elapsed: Duration) -> CgroupInfo {
+        let seconds = elapsed.as_secs_f64();
+        let rate = |now: u64, before: u64| {
+            if seconds > 0.0 {
+                now.saturating_sub(before) as f64 / seconds
+            } else {
+                0.0
+            }
+        };
+
+        CgroupInfo {
+            path: path.to_string(),
+            cpu: rate(self.counters.usage_usec, previous.usage_usec) / 10_000.0,
+            mem: self.mem,
+            mem_max: self.mem_max,
+            io_r: rate(self.counters.rbytes, previous.rbytes) as u64,
+            io_w: rate(self.counters.wbytes, previous.wbytes) as u64,
+            pids: self.pids,
+        }
+    }
+}
+
+/// The `usage_usec` line of `cpu.stat`.
+pub fn parse_usage_usec(stat: &str) -> u64 {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .and_then(|usage| usage.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+/// Bytes read and written of all devices in `io.stat`, with lines like
+/// `8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0`.
+pub fn parse_io_stat(stat: &str) -> (u64, u64) {
+    stat.split_whitespace()
+        .filter_map(|field| field.split_once('='))
+        .fold((0, 0), |(r, w), (key, value)| {
+            let value = value.parse::<u64>().unwrap_or_default();
+
+            match key {
+                "rbytes" => (r + value, w),
+                "wbytes" => (r, w + value),
+                _ => (r, w),
+            }
+        })
+}
+
+/// A limit like `memory.max`, which is `max` when there's none.
+pub fn parse_max(limit: &str) -> Option<u64> {
+    limit.trim().parse().ok()
+}
+
+/// Reads every cgroup below `root` by path. Empty if `root` isn't a cgroup v2 hierarchy,
+/// like on systems still mounting v1 there.
+pub fn read_cgroups(root: &Path) -> BTreeMap<String, Cgroup> {
+    let mut cgroups = BTreeMap::new();
+    if !root.join("cgroup.controllers").exists() {
+        return cgroups;
+    }
+
+    let mut pending = vec![("/".to_string(), root.to_path_buf())];
+    while let Some((path, dir)) = pending.pop() {
+        cgroups.insert(path.clone(), Cgroup::read(&dir));
+
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let child = format!("{}/{name}", path.trim_end_matches('/'));
+                pending.push((child, entry.path()));
+            }
+        }
+    }
+
+    cgroups
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn write_group(root: &Path, path: &str, files: &[(&str, &str)]) {
+        let dir = root.join(path.trim_start_matches('/'));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            fs::write(dir.join(file), format!("{contents}\n")).unwrap();
+        }
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("jwtop-cgroups-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(
+            parse_usage_usec("usage_usec 1500\nuser_usec 1000\nsystem_usec 500\n"),
+            1500
+        );
+        assert_eq!(
+            parse_io_stat(
+                "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0\n\
+                 259:0 rbytes=1 wbytes=2 rios=1 wios=1 dbytes=0 dios=0\n"
+            ),
+            (1025, 2050)
+        );
+        assert_eq!(parse_max("max\n"), None);
+        assert_eq!(parse_max("1073741824\n"), Some(1 << 30));
+    }
+
+    #[test]
+    fn paths() {
+        let info = |path: &str| CgroupInfo {
+            path: path.into(),
+            ..Default::default()
+        };
+
+        assert_eq!(info("/").name(), "/");
+        assert_eq!(info("/").parent(), None);
+        assert_eq!(info("/system.slice").name(), "system.slice");
+        assert_eq!(info("/system.slice").parent(), Some("/"));
+        assert_eq!(
+            info("/system.slice/a.service").parent(),
+            Some("/system.slice")
+        );
+    }
+
+    #[test]
+    fn hierarchy() {
+        let root = temp_root("hierarchy");
+        write_group(
+            &root,
+            "/",
+            &[
+                ("cgroup.controllers", "cpu io memory pids"),
+                ("cpu.stat", "usage_usec 0"),
+            ],
+        );
+        write_group(
+            &root,
+            "/system.slice",
+            &[
+                ("cpu.stat", "usage_usec 1000000"),
+                ("memory.current", "4096"),
+                ("memory.max", "8192"),
+                ("io.stat", "8:0 rbytes=100 wbytes=200"),
+                ("pids.current", "3"),
+            ],
+        );
+        write_group(&root, "/system.slice/a.service", &[("memory.max", "max")]);
+
+        let cgroups = read_cgroups(&root);
+        assert_eq!(
+            cgroups.keys().collect::<Vec<_>>(),
+            ["/", "/system.slice", "/system.slice/a.service"]
+        );
+
+        let previous = CgroupCounters {
+            usage_usec: 500_000,
+            rbytes: 0,
+            wbytes: 100,
+        };
+        let info =
+            cgroups["/system.slice"].since("/system.slice", &previous, Duration::from_secs(1));
+        assert_eq!(info.cpu, 50.0);
+        assert_eq!(info.mem, 4096);
+        assert_eq!(info.mem_max, Some(8192));
+        assert_eq!((info.io_r, info.io_w), (100, 100));
+        assert_eq!(info.pids, 3);
+        assert_eq!(cgroups["/system.slice/a.service"].mem_max, None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn not_v2() {
+        let root = temp_root("v1");
+        write_group(&root, "/cpu", &[]);
+
+        assert!(read_cgroups(&root).is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+pub const PROC_STAT: &str = "/proc/stat";
+
+/// Share of time a cpu spent in each category in percent. Idle time is left out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CpuTimes {
+    /// Userspace, including niced processes.
+    pub user: f64,
+    pub system: f64,
+    /// Idle while waiting for IO.
+    pub iowait: f64,
+    /// Hard and soft interrupts.
+    pub irq: f64,
+    /// Taken by the hypervisor for other guests.
+    pub steal: f64,
+}
+
+impl CpuTimes {
+    pub const NAMES: [&'static str; 5] = ["user", "system", "iowait", "irq", "steal"];
+
+    /// The categories in the order of [`CpuTimes::NAMES`].
+    pub fn values(&self) -> [f64; 5] {
+        [self.user, self.system, self.iowait, self.irq, self.steal]
+    }
+}
+
+/// Counters of a single `cpu` line of `/proc/stat`, in clock ticks since boot.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CpuTicks {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+}
+
+impl CpuTicks {
+    fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    /// How the time between `previous` and `self` was spent.
+    pub fn since(&self, previous: &CpuTicks) -> CpuTimes {
+        let total = self.total().saturating_sub(previous.total());
+        if total == 0 {
+            return CpuTimes::default();
+        }
+
+        let percent =
+            |now: u64, before: u64| now.saturating_sub(before) as f64 / total as f64 * 100.0;
+
+        CpuTimes {
+            user: percent(self.user + self.nice, previous.user + previous.nice),
+            system: percent(self.system, previous.system),
+            iowait: percent(self.iowait, previous.iowait),
+            irq: percent(self.irq + self.softirq, previous.irq + previous.softirq),
+            steal: percent(self.steal, previous.steal),
+        }
+    }
+}
+
+/// Parses the `cpu` lines of `/proc/stat`. The first entry is the aggregate of all cpus,
+/// followed by every cpu in order.
+pub fn parse_proc_stat(stat: &str) -> Vec<CpuTicks> {
+    stat.lines()
+        .filter(|l| l.starts_with("cpu"))
+        .map(|line| {
+            let mut fields = line
+                .split_whitespace()
+                .skip(1)
+                .map(|f| f.parse::<u64>().unwrap_or_default());
+            let mut next = || fields.next().unwrap_or_default();
+
+            CpuTicks {
+                user: next(),
+                nice: next(),
+                system: next(),
+                idle: next(),
+                iowait: next(),
+                irq: next(),
+                softirq: next(),
+                steal: next(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let before = parse_proc_stat(
+            "cpu  100 0 50 800 20 5 5 20 0 0\n\
+             cpu0 100 0 50 800 20 5 5 20 0 0\n\
+             intr 1 2 3\n",
+        );
+        let after = parse_proc_stat(
+            "cpu  150 10 70 880 40 10 10 30 0 0\n\
+             cpu0 150 10 70 880 40 10 10 30 0 0\n\
+             intr 1 2 3\n",
+        );
+
+        assert_eq!(before.len(), 2);
+
+        let times = after[0].since(&before[0]);
+        assert_eq!(
+            times,
+            CpuTimes {
+                user: 30.0,
+                system: 10.0,
+                iowait: 10.0,
+                irq: 5.0,
+                steal: 5.0,
+            }
+        );
+
+        assert_eq!(after[1].since(&after[1]), CpuTimes::default());
+    }
+
+    #[test]
+    fn short_lines() {
+        let ticks = parse_proc_stat("cpu 1 2 3 4\n");
+        assert_eq!(ticks[0].idle, 4);
+        assert_eq!(ticks[0].steal, 0);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use super::DiskInfo;
+
+pub const PROC_DISKSTATS: &str = "/proc/diskstats";
+
+/// Counters of a single line of `/proc/diskstats`, since boot.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiskCounters {
+    reads: u64,
+    read_sectors: u64,
+    read_ms: u64,
+    writes: u64,
+    write_sectors: u64,
+    write_ms: u64,
+    /// Requests currently in flight, the only field that isn't a counter.
+    in_flight: u64,
+    io_ms: u64,
+    weighted_ms: u64,
+}
+
+impl DiskCounters {
+    /// What the disk did between `previous` and `self`, taken `elapsed` apart.
+    pub fn since(&self, previous: &DiskCounters, elapsed: Duration) -> DiskInfo {
+        DiskInfo {
+            r_sectors: self.read_sectors.saturating_sub(previous.read_sectors) as usize,
+            w_sectors: self.write_sectors.saturating_sub(previous.write_sectors) as usize,
+            reads: self.reads.saturating_sub(previous.reads),
+            writes: self.writes.saturating_sub(previous.writes),
+            read_ms: self.read_ms.saturating_sub(previous.read_ms),
+            write_ms: self.write_ms.saturating_sub(previous.write_ms),
+            in_flight: self.in_flight,
+            io_ms: self.io_ms.saturating_sub(previous.io_ms),
+            weighted_ms: self.weighted_ms.saturating_sub(previous.weighted_ms),
+            elapsed_ms: elapsed.as_millis() as u64,
+        }
+    }
+}
+
+/// Parses `/proc/diskstats` into the counters of every device by name. Fields added by
+/// newer kernels after the weighted time, like discards and flushes, are ignored.
+pub fn parse_diskstats(stats: &str) -> BTreeMap<String, DiskCounters> {
+    stats
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(2);
+            let name = fields.next()?.to_string();
+
+            let mut fields = fields.map(|f| f.parse::<u64>().unwrap_or_default());
+            let mut next = || fields.next().unwrap_or_default();
+
+            let reads = next();
+            let _read_merges = next();
+            let read_sectors = next();
+            let read_ms = next();
+            let writes = next();
+            let _write_merges = next();
+            let write_sectors = next();
+            let write_ms = next();
+
+            let counters = DiskCounters {
+                reads,
+                read_sectors,
+                read_ms,
+                writes,
+                write_sectors,
+                write_ms,
+                in_flight: next(),
+                io_ms: next(),
+                weighted_ms: next(),
+            };
+
+            Some((name, counters))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let before = parse_diskstats(
+            "   8       0 sda 1000 10 8000 500 2000 20 16000 1500 0 1000 2000 0 0 0 0\n\
+             259       0 nvme0n1 100 0 800 50 200 0 1600 150 1 100 200\n",
+        );
+        let after = parse_diskstats(
+            "   8       0 sda 1100 10 8800 560 2100 20 17600 1640 2 1500 2400 0 0 0 0\n\
+             259       0 nvme0n1 100 0 800 50 200 0 1600 150 0 100 200\n",
+        );
+
+        assert_eq!(before.len(), 2);
+
+        let info = after["sda"].since(&before["sda"], Duration::from_secs(1));
+        assert_eq!(info.r_sectors, 800);
+        assert_eq!(info.w_sectors, 1600);
+        assert_eq!(info.iops(), [100.0, 100.0]);
+        assert_eq!(info.await_ms(), [0.6, 1.4]);
+        assert_eq!(info.utilization(), 50.0);
+        assert_eq!(info.queue_depth(), 0.4);
+        assert_eq!(info.in_flight, 2);
+
+        let slow = after["sda"].since(&before["sda"], Duration::from_secs(2));
+        assert_eq!(slow.bytes_per_second(), [204_800.0, 409_600.0]);
+
+        let idle = after["nvme0n1"].since(&before["nvme0n1"], Duration::from_secs(1));
+        assert_eq!(idle.iops(), [0.0, 0.0]);
+        assert_eq!(idle.await_ms(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn short_lines() {
+        let stats = parse_diskstats("8 0 sda 1 2 3\n\n8 1\n");
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["sda"].read_sectors, 3);
+        assert_eq!(stats["sda"].weighted_ms, 0);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use systemstat::Platform;
+
+pub const PROC_MOUNTS: &str = "/proc/mounts";
+
+/// How often the filesystems are read, as their usage changes slowly.
+const INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long the first snapshot waits for the filesystems to be read.
+const FIRST_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Space and inodes used on a mounted filesystem.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilesystemInfo {
+    pub mount_point: String,
+    pub fs_type: String,
+    /// Size in bytes.
+    pub total: u64,
+    /// Bytes in use.
+    pub used: u64,
+    /// Bytes left to unprivileged users, less than `total - used` by the reserved blocks.
+    pub avail: u64,
+    /// Inodes in total, 0 for filesystems allocating them on demand like btrfs.
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+}
+
+impl FilesystemInfo {
+    fn new(fs: &systemstat::Filesystem) -> Self {
+        Self {
+            mount_point: fs.fs_mounted_on.clone(),
+            fs_type: fs.fs_type.clone(),
+            total: fs.total.as_u64(),
+            used: fs.total.as_u64().saturating_sub(fs.free.as_u64()),
+            avail: fs.avail.as_u64(),
+            inodes_total: fs.files_total as u64,
+            inodes_used: fs.files_total.saturating_sub(fs.files_avail) as u64,
+        }
+    }
+
+    /// Used space in percent of what unprivileged users can use, like `df` reports it.
+    pub fn usage(&self) -> f64 {
+        let usable = self.used + self.avail;
+
+        if usable == 0 {
+            0.0
+        } else {
+            self.used as f64 / usable as f64 * 100.0
+        }
+    }
+
+    /// Used inodes in percent, `None` if the filesystem has no fixed amount of them.
+    pub fn inode_usage(&self) -> Option<f64> {
+        (self.inodes_total > 0).then(|| self.inodes_used as f64 / self.inodes_total as f64 * 100.0)
+    }
+}
+
+/// Reads the filesystems every [`INTERVAL`] from a thread of its own, as reading a stale
+/// network mount can block for as long as the server doesn't answer.
+pub(super) struct Sampler {
+    receiver: Receiver<Vec<FilesystemInfo>>,
+    latest: Vec<FilesystemInfo>,
+}
+
+impl Sampler {
+    /// Starts reading every filesystem of a type not in `exclude_types`.
+    pub(super) fn spawn(exclude_types: Vec<String>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let system = systemstat::System::new();
+            while sender.send(read(&system, &exclude_types)).is_ok() {
+                thread::sleep(INTERVAL);
+            }
+        });
+
+        let latest = receiver
+            .recv_timeout(FIRST_READ_TIMEOUT)
+            .unwrap_or_default();
+        Self { receiver, latest }
+    }
+
+    /// The filesystems as of the newest reading.
+    pub(super) fn latest(&mut self) -> &[FilesystemInfo] {
+        if let Some(filesystems) = self.receiver.try_iter().last() {
+            self.latest = filesystems;
+        }
+
+        &self.latest
+    }
+}
+
+/// The mount points in `/proc/mounts` of a filesystem type not in `exclude_types`.
+fn mount_points<'a>(mounts: &'a str, exclude_types: &[String]) -> Vec<&'a str> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            Some((fields.next()?, fields.next()?))
+        })
+        .filter(|(_, fs_type)| !exclude_types.iter().any(|t| t == fs_type))
+        .map(|(mount_point, _)| mount_point)
+        .collect()
+}
+
+/// Every mounted filesystem with a size, leaving out the likes of `proc` and `sysfs`, and
+/// those of a type in `exclude_types` before they are read.
+fn read(system: &systemstat::System, exclude_types: &[String]) -> Vec<FilesystemInfo> {
+    let mounts = match std::fs::read_to_string(PROC_MOUNTS) {
+        Ok(mounts) => mount_points(&mounts, exclude_types)
+            .into_iter()
+            .filter_map(|mount_point| system.mount_at(mount_point).ok())
+            .collect(),
+        // without it the types are only known once every filesystem was read
+        Err(_) => system
+            .mounts()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|fs| !exclude_types.contains(&fs.fs_type))
+            .collect::<Vec<_>>(),
+    };
+
+    mounts
+        .iter()
+        .filter(|fs| fs.total.as_u64() > 0)
+        .map(FilesystemInfo::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage() {
+        let fs = FilesystemInfo {
+            total: 1000,
+            used: 600,
+            // 100 bytes are reserved
+            avail: 300,
+            inodes_total: 200,
+            inodes_used: 50,
+            ..Default::default()
+        };
+        assert_eq!(fs.usage(), 600.0 / 900.0 * 100.0);
+        assert_eq!(fs.inode_usage(), Some(25.0));
+
+        let empty = FilesystemInfo::default();
+        assert_eq!(empty.usage(), 0.0);
+        assert_eq!(empty.inode_usage(), None);
+
+        // inodes allocated on demand
+        let btrfs = FilesystemInfo {
+            inodes_total: 0,
+            inodes_used: 10,
+            ..fs
+        };
+        assert_eq!(btrfs.inode_usage(), None);
+    }
+
+    #[test]
+    fn excluded_types() {
+        let mounts = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                      proc /proc proc rw,nosuid 0 0\n\
+                      server:/export /mnt/nfs nfs4 rw,hard 0 0\n\
+                      tmpfs /mnt/my\\040files tmpfs rw 0 0\n\
+                      \n";
+
+        assert_eq!(
+            mount_points(mounts, &[]),
+            ["/", "/proc", "/mnt/nfs", "/mnt/my\\040files"]
+        );
+        assert_eq!(
+            mount_points(mounts, &["proc".into(), "nfs4".into()]),
+            ["/", "/mnt/my\\040files"]
+        );
+    }
+}
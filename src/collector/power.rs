@@ -0,0 +1,269 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use systemstat::Platform;
+
+pub const DEFAULT_POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+/// What the batteries are doing, as reported by their `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChargeState {
+    Charging,
+    Discharging,
+    Full,
+    /// Plugged in but held below full, like batteries limited to 80% are.
+    NotCharging,
+    #[default]
+    Unknown,
+}
+
+impl ChargeState {
+    fn parse(status: &str) -> Self {
+        match status {
+            "Charging" => ChargeState::Charging,
+            "Discharging" => ChargeState::Discharging,
+            "Full" => ChargeState::Full,
+            "Not charging" => ChargeState::NotCharging,
+            _ => ChargeState::Unknown,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChargeState::Charging => "charging",
+            ChargeState::Discharging => "discharging",
+            ChargeState::Full => "full",
+            ChargeState::NotCharging => "not charging",
+            ChargeState::Unknown => "unknown",
+        }
+    }
+}
+
+/// All batteries of the system taken together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PowerInfo {
+    /// Charge in percent.
+    pub charge: f64,
+    pub state: ChargeState,
+    /// Watts flowing into or out of the batteries, if they report it.
+    pub power: Option<f64>,
+    /// Time until the batteries are empty while discharging, or full while charging.
+    pub time_remaining: Option<Duration>,
+    /// Whether external power is plugged in, if known.
+    pub ac_online: Option<bool>,
+}
+
+/// A single `BAT*` directory, with energies in µWh and power in µW. Batteries reporting
+/// charge in µAh and current in µA instead are converted using their voltage.
+#[derive(Debug, Default)]
+struct Battery {
+    state: ChargeState,
+    capacity: Option<f64>,
+    energy_now: Option<f64>,
+    energy_full: Option<f64>,
+    power_now: Option<f64>,
+}
+
+impl Battery {
+    fn read(dir: &Path) -> Self {
+        let read = |file: &str| {
+            fs::read_to_string(dir.join(file))
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+        let number = |file: &str| read(file).and_then(|s| s.parse::<f64>().ok());
+
+        // µAh and µA times µV are pWh and pW
+        let voltage = number("voltage_now");
+        let from_charge = |file: &str| Some(number(file)? * voltage? / 1_000_000.0);
+
+        Self {
+            state: read("status").map_or(ChargeState::Unknown, |s| ChargeState::parse(&s)),
+            capacity: number("capacity"),
+            energy_now: number("energy_now").or_else(|| from_charge("charge_now")),
+            energy_full: number("energy_full").or_else(|| from_charge("charge_full")),
+            power_now: number("power_now").or_else(|| from_charge("current_now")),
+        }
+    }
+}
+
+/// Reads every battery and mains supply below `root`. `None` if there are no batteries.
+pub fn read_power_supply(root: &Path) -> Option<PowerInfo> {
+    let mut batteries = Vec::new();
+    let mut ac_online = None;
+
+    for dir in fs::read_dir(root).ok()?.flatten() {
+        let path = dir.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+
+        match kind.trim() {
+            "Battery" => batteries.push(Battery::read(&path)),
+            "Mains" => {
+                let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+                ac_online = Some(ac_online.unwrap_or(false) || online.trim() == "1");
+            }
+            _ => {}
+        }
+    }
+
+    if batteries.is_empty() {
+        return None;
+    }
+
+    let sum = |field: fn(&Battery) -> Option<f64>| {
+        batteries
+            .iter()
+            .map(field)
+            .sum::<Option<f64>>()
+            .filter(|&sum| sum > 0.0)
+    };
+    let energy_now = sum(|b| b.energy_now);
+    let energy_full = sum(|b| b.energy_full);
+    let power_now = sum(|b| b.power_now);
+
+    let charge = match (energy_now, energy_full) {
+        (Some(now), Some(full)) => now / full * 100.0,
+        _ => {
+            let capacities = batteries.iter().filter_map(|b| b.capacity);
+            let count = capacities.clone().count().max(1);
+            capacities.sum::<f64>() / count as f64
+        }
+    };
+
+    // any battery charging or discharging decides the state of all of them
+    let state = [ChargeState::Discharging, ChargeState::Charging]
+        .into_iter()
+        .find(|&state| batteries.iter().any(|b| b.state == state))
+        .unwrap_or(batteries[0].state);
+
+    let time_remaining = match (state, energy_now, energy_full, power_now) {
+        (ChargeState::Discharging, Some(now), _, Some(power)) => Some(now / power),
+        (ChargeState::Charging, Some(now), Some(full), Some(power)) => Some((full - now) / power),
+        _ => None,
+    }
+    .map(|hours| Duration::from_secs_f64(hours.max(0.0) * 3600.0));
+
+    Some(PowerInfo {
+        charge: charge.clamp(0.0, 100.0),
+        state,
+        power: power_now.map(|microwatts| microwatts / 1_000_000.0),
+        time_remaining,
+        ac_online,
+    })
+}
+
+/// Reads the batteries from sysfs below `root` if it exists, or asks systemstat otherwise,
+/// like on systems without sysfs.
+pub(super) fn read(root: &Path, system: &systemstat::System) -> Option<PowerInfo> {
+    if root.exists() {
+        return read_power_supply(root);
+    }
+
+    let battery = system.battery_life().ok()?;
+    let ac_online = system.on_ac_power().ok();
+
+    Some(PowerInfo {
+        charge: (battery.remaining_capacity as f64 * 100.0).clamp(0.0, 100.0),
+        state: match ac_online {
+            Some(true) => ChargeState::Charging,
+            Some(false) => ChargeState::Discharging,
+            None => ChargeState::Unknown,
+        },
+        power: None,
+        time_remaining: (!battery.remaining_time.is_zero()).then_some(battery.remaining_time),
+        ac_online,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn write_supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            fs::write(dir.join(file), format!("{contents}\n")).unwrap();
+        }
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("jwtop-power-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn energy_batteries() {
+        let root = temp_root("energy");
+        write_supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        write_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "50"),
+                ("energy_now", "20000000"),
+                ("energy_full", "40000000"),
+                ("power_now", "10000000"),
+            ],
+        );
+        write_supply(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Unknown"),
+                ("capacity", "100"),
+                ("energy_now", "20000000"),
+                ("energy_full", "20000000"),
+                ("power_now", "0"),
+            ],
+        );
+
+        let info = read_power_supply(&root).unwrap();
+        assert_eq!(info.charge, 40_000_000.0 / 60_000_000.0 * 100.0);
+        assert_eq!(info.state, ChargeState::Discharging);
+        assert_eq!(info.power, Some(10.0));
+        assert_eq!(info.time_remaining, Some(Duration::from_secs(4 * 3600)));
+        assert_eq!(info.ac_online, Some(false));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn charge_batteries() {
+        let root = temp_root("charge");
+        write_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("charge_now", "3000000"),
+                ("charge_full", "4000000"),
+                ("current_now", "1000000"),
+                ("voltage_now", "12000000"),
+            ],
+        );
+
+        let info = read_power_supply(&root).unwrap();
+        assert_eq!(info.charge, 75.0);
+        assert_eq!(info.power, Some(12.0));
+        assert_eq!(info.time_remaining, Some(Duration::from_secs(3600)));
+        assert_eq!(info.ac_online, None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn no_batteries() {
+        let root = temp_root("none");
+        write_supply(&root, "AC", &[("type", "Mains"), ("online", "1")]);
+
+        assert_eq!(read_power_supply(&root), None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+pub const PROC_PRESSURE: &str = "/proc/pressure";
+
+/// Share of the time in percent that tasks were stalled, averaged over 10, 60 and 300
+/// seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PressureStats {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+}
+
+/// A single file of `/proc/pressure`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourcePressure {
+    /// Time some tasks were stalled on the resource.
+    pub some: PressureStats,
+    /// Time all non-idle tasks were stalled at once, zero where the kernel doesn't report it.
+    pub full: PressureStats,
+}
+
+/// Pressure stall information of cpu, memory and io.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    pub cpu: ResourcePressure,
+    pub memory: ResourcePressure,
+    pub io: ResourcePressure,
+}
+
+impl Pressure {
+    pub const NAMES: [&'static str; 6] = [
+        "cpu some", "cpu full", "mem some", "mem full", "io some", "io full",
+    ];
+
+    /// The stats in the order of [`Pressure::NAMES`].
+    pub fn stats(&self) -> [PressureStats; 6] {
+        [
+            self.cpu.some,
+            self.cpu.full,
+            self.memory.some,
+            self.memory.full,
+            self.io.some,
+            self.io.full,
+        ]
+    }
+
+    /// Undoes [`Pressure::stats`].
+    pub fn from_stats(stats: [PressureStats; 6]) -> Self {
+        let [cpu_some, cpu_full, memory_some, memory_full, io_some, io_full] = stats;
+
+        Self {
+            cpu: ResourcePressure {
+                some: cpu_some,
+                full: cpu_full,
+            },
+            memory: ResourcePressure {
+                some: memory_some,
+                full: memory_full,
+            },
+            io: ResourcePressure {
+                some: io_some,
+                full: io_full,
+            },
+        }
+    }
+}
+
+/// Parses a file of `/proc/pressure`, like
+/// `some avg10=0.12 avg60=0.05 avg300=0.01 total=123456`.
+pub fn parse_pressure(text: &str) -> ResourcePressure {
+    let mut pressure = ResourcePressure::default();
+
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let stats = match fields.next() {
+            Some("some") => &mut pressure.some,
+            Some("full") => &mut pressure.full,
+            _ => continue,
+        };
+
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            let value = value.parse().unwrap_or_default();
+
+            match key {
+                "avg10" => stats.avg10 = value,
+                "avg60" => stats.avg60 = value,
+                "avg300" => stats.avg300 = value,
+                _ => {}
+            }
+        }
+    }
+
+    pressure
+}
+
+/// Reads the pressure files below `root`. `None` if the kernel has no PSI, which it only has
+/// from linux 4.20 on and if enabled.
+pub fn read_pressure(root: &Path) -> Option<Pressure> {
+    let read = |file: &str| fs::read_to_string(root.join(file)).ok();
+
+    Some(Pressure {
+        cpu: parse_pressure(&read("cpu")?),
+        memory: read("memory")
+            .map(|m| parse_pressure(&m))
+            .unwrap_or_default(),
+        io: read("io").map(|io| parse_pressure(&io)).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let pressure = parse_pressure(
+            "some avg10=1.50 avg60=0.75 avg300=0.25 total=123456\n\
+             full avg10=0.50 avg60=0.00 avg300=0.00 total=6543\n",
+        );
+
+        assert_eq!(
+            pressure.some,
+            PressureStats {
+                avg10: 1.5,
+                avg60: 0.75,
+                avg300: 0.25,
+            }
+        );
+        assert_eq!(pressure.full.avg10, 0.5);
+    }
+
+    #[test]
+    fn without_full() {
+        // kernels before 5.13 only report some cpu pressure
+        let pressure = parse_pressure("some avg10=2.00 avg60=1.00 avg300=0.50 total=1\n");
+
+        assert_eq!(pressure.some.avg10, 2.0);
+        assert_eq!(pressure.full, PressureStats::default());
+    }
+
+    #[test]
+    fn missing() {
+        let root = std::env::temp_dir().join(format!("jwtop-no-psi-{}", std::process::id()));
+        assert_eq!(read_pressure(&root), None);
+    }
+}
@@ -1,12 +1,18 @@
+mod charts;
 mod color;
+mod keys;
 mod theme;
 
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 
+pub use charts::{
+    ChartGraph, ChartMarker, ChartOptions, ChartsConfig, LegendPosition, StatsDisplay,
+};
 pub use color::SerdeColor;
+pub use keys::{Action, KeyBindings, KeyChord, Scope};
 pub use theme::*;
 
 #[derive(clap::Parser)]
@@ -23,15 +29,244 @@ pub struct Cli {
     /// The path to the config directory
     #[arg(long)]
     pub config_path: Option<PathBuf>,
+
+    /// Record every sample to a file, which can be viewed later with --replay
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Play back a file made with --record instead of monitoring this system
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Print every sample as a line of json instead of showing the interface
+    #[arg(long, visible_alias = "headless", conflicts_with_all = ["record", "replay"])]
+    pub json: bool,
+
+    /// With --json, stop after printing this many samples
+    #[arg(long, requires = "json")]
+    pub count: Option<u64>,
+
+    /// With --json or --agent, the time between samples in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    pub interval: u64,
+
+    /// With --json, how many of the processes using the most cpu to print
+    #[arg(long, requires = "json", default_value_t = 10)]
+    pub top: usize,
+
+    /// Serve the sampled metrics for prometheus on http://ADDR/metrics
+    #[arg(long, value_name = "ADDR", conflicts_with = "replay")]
+    pub serve_metrics: Option<String>,
+
+    /// Append a row of metrics for every sample to a csv file
+    #[arg(long, value_name = "FILE")]
+    pub log_csv: Option<PathBuf>,
+
+    /// Stream samples to clients connecting to ADDR (host:port or unix:/path) instead of
+    /// showing the interface
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["json", "replay", "connect"])]
+    pub agent: Option<crate::remote::Address>,
+
+    /// Show samples streamed from an agent at ADDR (host:port or unix:/path) instead of
+    /// monitoring this system
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["json", "replay"])]
+    pub connect: Option<crate::remote::Address>,
+
+    /// Show a table of every host from the [hosts] config section instead of monitoring
+    /// this system
+    #[arg(long, conflicts_with_all = ["json", "replay", "connect", "agent"])]
+    pub overview: bool,
+}
+
+mod csv_defaults {
+    pub fn max_size() -> u64 {
+        10 * 1024 * 1024
+    }
+
+    pub fn max_files() -> usize {
+        5
+    }
+
+    pub fn top() -> usize {
+        10
+    }
+}
+
+/// The `[log_csv]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvLogConfig {
+    pub path: PathBuf,
+    /// Size in bytes after which a file is rotated.
+    #[serde(default = "csv_defaults::max_size")]
+    pub max_size: u64,
+    /// How many rotated files are kept.
+    #[serde(default = "csv_defaults::max_files")]
+    pub max_files: usize,
+    /// A second file the processes using the most cpu are logged to.
+    #[serde(default)]
+    pub processes_path: Option<PathBuf>,
+    /// How many processes are logged every sample.
+    #[serde(default = "csv_defaults::top")]
+    pub top: usize,
+}
+
+impl CsvLogConfig {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_size: csv_defaults::max_size(),
+            max_files: csv_defaults::max_files(),
+            processes_path: None,
+            top: csv_defaults::top(),
+        }
+    }
+}
+
+mod history_defaults {
+    pub fn retention_days() -> u64 {
+        7
+    }
+
+    pub fn max_size() -> u64 {
+        32 * 1024 * 1024
+    }
+}
+
+/// The `[history]` config section. The long-term history is kept between runs only if it's
+/// present.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// The file the history is kept in, `history.bin` in the data directory if unset.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Days after which points are dropped from the file.
+    #[serde(default = "history_defaults::retention_days")]
+    pub retention_days: u64,
+    /// Size in bytes after which the file is compacted.
+    #[serde(default = "history_defaults::max_size")]
+    pub max_size: u64,
+}
+
+mod filesystems_defaults {
+    pub fn exclude_types() -> Vec<String> {
+        ["tmpfs", "devtmpfs", "overlay", "squashfs"]
+            .map(String::from)
+            .to_vec()
+    }
+}
+
+/// The `[filesystems]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesystemsConfig {
+    /// Types of the filesystems left out of the filesystems pane, which aren't even read.
+    #[serde(default = "filesystems_defaults::exclude_types")]
+    pub exclude_types: Vec<String>,
+}
+
+impl Default for FilesystemsConfig {
+    fn default() -> Self {
+        Self {
+            exclude_types: filesystems_defaults::exclude_types(),
+        }
+    }
+}
+
+/// What an alert watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Average usage of all cpus in percent.
+    Cpu,
+    /// Usage of the busiest cpu, or of `core` if set, in percent.
+    CoreCpu,
+    /// Used memory in percent.
+    Mem,
+    /// MB read per second from all disks, or from `disk` if set.
+    DiskRead,
+    /// MB written per second to all disks, or to `disk` if set.
+    DiskWrite,
+    /// Summed cpu usage in percent of the processes called `process`.
+    ProcessCpu,
+    /// Summed memory in MB of the processes called `process`.
+    ProcessMem,
+}
+
+/// An entry of the `[[alerts]]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// The alert triggers once the metric is at or above this.
+    pub threshold: f64,
+    /// Seconds the metric has to stay at or above the threshold before the alert triggers.
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub core: Option<usize>,
+    #[serde(default)]
+    pub disk: Option<String>,
+    #[serde(default)]
+    pub process: Option<String>,
+    /// A shell command run whenever the alert triggers.
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+impl AlertConfig {
+    fn validate(&self) -> Result<()> {
+        let needs_process = matches!(
+            self.metric,
+            AlertMetric::ProcessCpu | AlertMetric::ProcessMem
+        );
+
+        if needs_process && self.process.is_none() {
+            return Err(anyhow!("alert \"{}\" needs a process name", self.name));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Config {
     pub theme: Theme,
+    pub keys: KeyBindings,
+    pub log_csv: Option<CsvLogConfig>,
+    pub history: Option<HistoryConfig>,
+    /// Names and agent addresses of the hosts in the overview.
+    pub hosts: Vec<(String, crate::remote::Address)>,
+    pub alerts: Vec<AlertConfig>,
+    /// Where cpu frequencies and governors are read from, instead of
+    /// [`crate::collector::DEFAULT_CPUFREQ_ROOT`].
+    pub cpufreq_root: Option<PathBuf>,
+    /// Where batteries are read from, instead of
+    /// [`crate::collector::DEFAULT_POWER_SUPPLY_ROOT`].
+    pub power_supply_root: Option<PathBuf>,
+    /// Where cgroups are read from, instead of [`crate::collector::DEFAULT_CGROUP_ROOT`].
+    pub cgroup_root: Option<PathBuf>,
+    /// What the cpu chart shows at startup.
+    pub cpu_view: crate::app::CpuView,
+    /// What the disks chart shows at startup.
+    pub disk_view: crate::app::DiskView,
+    pub charts: ChartsConfig,
+    pub filesystems: FilesystemsConfig,
 }
 
 impl Config {
     pub fn load(cli: &Cli) -> Result<Self> {
+        let mut config = Self::load_file(cli)?;
+
+        if let Some(path) = &cli.log_csv {
+            match &mut config.log_csv {
+                Some(log_csv) => log_csv.path = path.clone(),
+                None => config.log_csv = Some(CsvLogConfig::new(path.clone())),
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn load_file(cli: &Cli) -> Result<Self> {
         let Some(config_dir_path) = config_path(cli)? else {
             return Ok(Default::default());
         };
@@ -55,30 +290,94 @@ impl Config {
             )
         })?;
 
-        let Some(theme) = config.theme else {
-            return Ok(Self {
-                theme: Default::default(),
-            });
-        };
+        let keys = KeyBindings::from_raw(config.keys.unwrap_or_default()).with_context(|| {
+            format!(
+                "Invalid key bindings in {}",
+                config_file_path.to_string_lossy()
+            )
+        })?;
 
-        let theme = if theme == "default" {
-            Theme::default()
-        } else {
-            Theme::load_from_file(&config_dir_path.join("themes").join(format!("{theme}.toml")))?
+        let hosts = config
+            .hosts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, address)| {
+                let address = address.parse().with_context(|| {
+                    format!(
+                        "Invalid address of host \"{name}\" in {}",
+                        config_file_path.to_string_lossy()
+                    )
+                })?;
+
+                Ok((name, address))
+            })
+            .collect::<Result<_>>()?;
+
+        let alerts = config.alerts.unwrap_or_default();
+        for alert in &alerts {
+            alert.validate().with_context(|| {
+                format!("Invalid alert in {}", config_file_path.to_string_lossy())
+            })?;
+        }
+
+        let theme = match config.theme {
+            None => Theme::default(),
+            Some(theme) if theme == "default" => Theme::default(),
+            Some(theme) => Theme::load_from_file(
+                &config_dir_path.join("themes").join(format!("{theme}.toml")),
+            )?,
         };
 
-        Ok(Self { theme })
+        Ok(Self {
+            theme,
+            keys,
+            log_csv: config.log_csv,
+            history: config.history,
+            hosts,
+            alerts,
+            cpufreq_root: config.cpufreq_root,
+            power_supply_root: config.power_supply_root,
+            cgroup_root: config.cgroup_root,
+            cpu_view: config.cpu_view.unwrap_or_default(),
+            disk_view: config.disk_view.unwrap_or_default(),
+            charts: config.charts.unwrap_or_default(),
+            filesystems: config.filesystems.unwrap_or_default(),
+        })
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct RawConfig {
     theme: Option<String>,
+    cpu_view: Option<crate::app::CpuView>,
+    disk_view: Option<crate::app::DiskView>,
+    cpufreq_root: Option<PathBuf>,
+    power_supply_root: Option<PathBuf>,
+    cgroup_root: Option<PathBuf>,
+    log_csv: Option<CsvLogConfig>,
+    history: Option<HistoryConfig>,
+    keys: Option<BTreeMap<String, keys::RawChords>>,
+    charts: Option<ChartsConfig>,
+    filesystems: Option<FilesystemsConfig>,
+    hosts: Option<BTreeMap<String, String>>,
+    alerts: Option<Vec<AlertConfig>>,
 }
 
 pub fn sample_config() -> String {
     toml::to_string_pretty(&RawConfig {
         theme: Some("default".into()),
+        cpu_view: Some(Default::default()),
+        disk_view: Some(Default::default()),
+        cpufreq_root: None,
+        power_supply_root: None,
+        cgroup_root: None,
+        log_csv: None,
+        history: None,
+        keys: Some(KeyBindings::default().to_raw()),
+        charts: Some(Default::default()),
+        filesystems: Some(Default::default()),
+        hosts: None,
+        alerts: None,
     })
     .unwrap()
 }
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use tui::{symbols::Marker, widgets::GraphType};
+
+use crate::app::Pane;
+
+/// The symbol charts are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartMarker {
+    #[default]
+    Braille,
+    Dot,
+    Block,
+    Bar,
+}
+
+impl ChartMarker {
+    pub fn next(self) -> Self {
+        match self {
+            ChartMarker::Braille => ChartMarker::Dot,
+            ChartMarker::Dot => ChartMarker::Block,
+            ChartMarker::Block => ChartMarker::Bar,
+            ChartMarker::Bar => ChartMarker::Braille,
+        }
+    }
+
+    pub fn marker(self) -> Marker {
+        match self {
+            ChartMarker::Braille => Marker::Braille,
+            ChartMarker::Dot => Marker::Dot,
+            ChartMarker::Block => Marker::Block,
+            ChartMarker::Bar => Marker::Bar,
+        }
+    }
+}
+
+/// Whether samples are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartGraph {
+    #[default]
+    Line,
+    Scatter,
+}
+
+impl ChartGraph {
+    pub fn next(self) -> Self {
+        match self {
+            ChartGraph::Line => ChartGraph::Scatter,
+            ChartGraph::Scatter => ChartGraph::Line,
+        }
+    }
+
+    pub fn graph_type(self) -> GraphType {
+        match self {
+            ChartGraph::Line => GraphType::Line,
+            ChartGraph::Scatter => GraphType::Scatter,
+        }
+    }
+}
+
+/// The corner of a chart its legend is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegendPosition {
+    #[default]
+    TopRight,
+    TopLeft,
+    BottomLeft,
+    BottomRight,
+    Hidden,
+}
+
+impl LegendPosition {
+    pub fn next(self) -> Self {
+        match self {
+            LegendPosition::TopRight => LegendPosition::TopLeft,
+            LegendPosition::TopLeft => LegendPosition::BottomLeft,
+            LegendPosition::BottomLeft => LegendPosition::BottomRight,
+            LegendPosition::BottomRight => LegendPosition::Hidden,
+            LegendPosition::Hidden => LegendPosition::TopRight,
+        }
+    }
+}
+
+/// Where a chart shows the minimum, average, maximum and 95th percentile of its series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsDisplay {
+    #[default]
+    Hidden,
+    /// After the name of every series in the legend.
+    Legend,
+    /// In a table replacing the legend.
+    Table,
+}
+
+impl StatsDisplay {
+    pub fn next(self) -> Self {
+        match self {
+            StatsDisplay::Hidden => StatsDisplay::Legend,
+            StatsDisplay::Legend => StatsDisplay::Table,
+            StatsDisplay::Table => StatsDisplay::Hidden,
+        }
+    }
+}
+
+/// How a single chart is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartOptions {
+    pub marker: ChartMarker,
+    pub graph: ChartGraph,
+    /// Fill the area below every curve.
+    pub fill: bool,
+    /// Use a logarithmic y axis, for values spanning orders of magnitude like disk rates.
+    pub log_scale: bool,
+    /// Space in percent left above the largest value on axes scaled to the data.
+    pub headroom: u32,
+    /// Percent by which such an axis drops for every new sample once the data falls below
+    /// it, so that it doesn't jump after every spike. 100 follows the data right away.
+    pub axis_decay: u32,
+    /// Label the x axis with the age of the samples.
+    pub time_labels: bool,
+    pub legend: LegendPosition,
+    pub stats: StatsDisplay,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            marker: Default::default(),
+            graph: Default::default(),
+            fill: false,
+            log_scale: false,
+            headroom: 10,
+            axis_decay: 10,
+            time_labels: true,
+            legend: Default::default(),
+            stats: Default::default(),
+        }
+    }
+}
+
+/// The `[charts]` config section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartsConfig {
+    pub cpu: ChartOptions,
+    pub battery: ChartOptions,
+    pub mem: ChartOptions,
+    pub disks: ChartOptions,
+}
+
+impl ChartsConfig {
+    /// The options of the chart in the pane, if it has one.
+    pub fn get(&self, pane: Pane) -> Option<&ChartOptions> {
+        match pane {
+            Pane::Cpu => Some(&self.cpu),
+            Pane::Battery => Some(&self.battery),
+            Pane::Memory => Some(&self.mem),
+            Pane::Disks => Some(&self.disks),
+            Pane::Processes | Pane::Filesystems | Pane::Pressure | Pane::Cgroups => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, pane: Pane) -> Option<&mut ChartOptions> {
+        match pane {
+            Pane::Cpu => Some(&mut self.cpu),
+            Pane::Battery => Some(&mut self.battery),
+            Pane::Memory => Some(&mut self.mem),
+            Pane::Disks => Some(&mut self.disks),
+            Pane::Processes | Pane::Filesystems | Pane::Pressure | Pane::Cgroups => None,
+        }
+    }
+}
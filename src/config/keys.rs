@@ -0,0 +1,759 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// The input mode an action is available in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Available in every input mode.
+    Global,
+    /// Available while browsing the processes table.
+    Normal,
+    /// Available while typing a process search.
+    Search,
+    /// Available while the series list of a chart is open.
+    Series,
+    /// Available while the hosts overview is shown, together with the
+    /// [`Scope::Normal`] actions.
+    Overview,
+    /// Available while the cgroups pane is focused, together with the [`Scope::Normal`]
+    /// actions.
+    Cgroups,
+    /// Available while the help overlay is open.
+    Help,
+}
+
+impl Scope {
+    pub const ALL: &'static [Scope] = &[
+        Scope::Global,
+        Scope::Normal,
+        Scope::Overview,
+        Scope::Cgroups,
+        Scope::Search,
+        Scope::Series,
+        Scope::Help,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::Global => "everywhere",
+            Scope::Normal => "browsing processes",
+            Scope::Search => "searching processes",
+            Scope::Overview => "hosts overview",
+            Scope::Cgroups => "cgroup tree",
+            Scope::Series => "series list",
+            Scope::Help => "help",
+        }
+    }
+
+    fn overlaps(self, other: Scope) -> bool {
+        self == other
+            || self == Scope::Global
+            || other == Scope::Global
+            || matches!(
+                (self, other),
+                (Scope::Normal, Scope::Overview)
+                    | (Scope::Overview, Scope::Normal)
+                    | (Scope::Normal, Scope::Cgroups)
+                    | (Scope::Cgroups, Scope::Normal)
+            )
+    }
+}
+
+/// Everything a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    ForceQuit,
+    Quit,
+    Search,
+    SortPid,
+    SortName,
+    SortCpu,
+    SortMemory,
+    SortDiskRead,
+    SortDiskWrite,
+    SortLoad,
+    CycleCpuView,
+    CycleDiskView,
+    FocusNext,
+    FocusPrevious,
+    CycleChartMarker,
+    CycleChartGraph,
+    ToggleChartFill,
+    ToggleLogScale,
+    OpenSeriesList,
+    CycleStats,
+    ZoomOut,
+    ZoomIn,
+    ExitSearch,
+    ClearSearch,
+    DeleteChar,
+    Pause,
+    ScrubBack,
+    ScrubForward,
+    TogglePlayback,
+    PlaybackFaster,
+    PlaybackSlower,
+    SeekBack,
+    SeekForward,
+    ToggleHelp,
+    CloseHelp,
+    HelpUp,
+    HelpDown,
+    SelectPrevious,
+    SelectNext,
+    OpenHost,
+    CloseHost,
+    CloseSeriesList,
+    SeriesPrevious,
+    SeriesNext,
+    ToggleSeries,
+    SoloSeries,
+    CycleLegend,
+    CgroupUp,
+    CgroupDown,
+    ToggleCgroup,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::ForceQuit,
+        Action::Quit,
+        Action::Search,
+        Action::SortPid,
+        Action::SortName,
+        Action::SortCpu,
+        Action::SortMemory,
+        Action::SortDiskRead,
+        Action::SortDiskWrite,
+        Action::SortLoad,
+        Action::CycleCpuView,
+        Action::CycleDiskView,
+        Action::FocusNext,
+        Action::FocusPrevious,
+        Action::CycleChartMarker,
+        Action::CycleChartGraph,
+        Action::ToggleChartFill,
+        Action::ToggleLogScale,
+        Action::OpenSeriesList,
+        Action::CycleStats,
+        Action::ZoomOut,
+        Action::ZoomIn,
+        Action::ExitSearch,
+        Action::ClearSearch,
+        Action::DeleteChar,
+        Action::Pause,
+        Action::ScrubBack,
+        Action::ScrubForward,
+        Action::TogglePlayback,
+        Action::PlaybackFaster,
+        Action::PlaybackSlower,
+        Action::SeekBack,
+        Action::SeekForward,
+        Action::ToggleHelp,
+        Action::CloseHelp,
+        Action::HelpUp,
+        Action::HelpDown,
+        Action::SelectPrevious,
+        Action::SelectNext,
+        Action::OpenHost,
+        Action::CloseHost,
+        Action::CloseSeriesList,
+        Action::SeriesPrevious,
+        Action::SeriesNext,
+        Action::ToggleSeries,
+        Action::SoloSeries,
+        Action::CycleLegend,
+        Action::CgroupUp,
+        Action::CgroupDown,
+        Action::ToggleCgroup,
+    ];
+
+    /// The name used for this action in the `[keys]` config section.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::ForceQuit => "force_quit",
+            Action::Quit => "quit",
+            Action::Search => "search",
+            Action::SortPid => "sort_pid",
+            Action::SortName => "sort_name",
+            Action::SortCpu => "sort_cpu",
+            Action::SortMemory => "sort_memory",
+            Action::SortDiskRead => "sort_disk_read",
+            Action::SortDiskWrite => "sort_disk_write",
+            Action::SortLoad => "sort_load",
+            Action::CycleCpuView => "cpu_view",
+            Action::CycleDiskView => "disk_view",
+            Action::FocusNext => "focus_next",
+            Action::FocusPrevious => "focus_previous",
+            Action::CycleChartMarker => "chart_marker",
+            Action::CycleChartGraph => "chart_graph",
+            Action::ToggleChartFill => "chart_fill",
+            Action::ToggleLogScale => "chart_log_scale",
+            Action::OpenSeriesList => "series",
+            Action::CycleStats => "chart_stats",
+            Action::ZoomOut => "zoom_out",
+            Action::ZoomIn => "zoom_in",
+            Action::ExitSearch => "exit_search",
+            Action::ClearSearch => "clear_search",
+            Action::DeleteChar => "delete_char",
+            Action::Pause => "pause",
+            Action::ScrubBack => "scrub_back",
+            Action::ScrubForward => "scrub_forward",
+            Action::TogglePlayback => "toggle_playback",
+            Action::PlaybackFaster => "playback_faster",
+            Action::PlaybackSlower => "playback_slower",
+            Action::SeekBack => "seek_back",
+            Action::SeekForward => "seek_forward",
+            Action::ToggleHelp => "help",
+            Action::CloseHelp => "close_help",
+            Action::HelpUp => "help_up",
+            Action::HelpDown => "help_down",
+            Action::SelectPrevious => "select_previous",
+            Action::SelectNext => "select_next",
+            Action::OpenHost => "open_host",
+            Action::CloseHost => "close_host",
+            Action::CloseSeriesList => "close_series",
+            Action::SeriesPrevious => "series_previous",
+            Action::SeriesNext => "series_next",
+            Action::ToggleSeries => "toggle_series",
+            Action::SoloSeries => "solo_series",
+            Action::CycleLegend => "legend_position",
+            Action::CgroupUp => "cgroup_up",
+            Action::CgroupDown => "cgroup_down",
+            Action::ToggleCgroup => "toggle_cgroup",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::ForceQuit => "quit from any mode",
+            Action::Quit => "quit",
+            Action::Search => "search processes by name",
+            Action::SortPid => "sort processes by pid, or cgroups by pid count",
+            Action::SortName => "sort processes, hosts or cgroups by name",
+            Action::SortCpu => "sort processes, hosts or cgroups by cpu usage",
+            Action::SortMemory => "sort processes, hosts or cgroups by memory usage",
+            Action::SortDiskRead => "sort processes, hosts or cgroups by disk reads",
+            Action::SortDiskWrite => "sort processes, hosts or cgroups by disk writes",
+            Action::SortLoad => "sort hosts by load average",
+            Action::CycleCpuView => "switch what the cpu chart shows",
+            Action::CycleDiskView => "switch what the disks chart shows",
+            Action::FocusNext => "focus the next pane",
+            Action::FocusPrevious => "focus the previous pane",
+            Action::CycleChartMarker => "switch the symbol the focused chart is drawn with",
+            Action::CycleChartGraph => "switch the focused chart between lines and points",
+            Action::ToggleChartFill => "fill the area below the focused chart's curves",
+            Action::ToggleLogScale => "switch the focused chart to a logarithmic scale",
+            Action::OpenSeriesList => "choose the series the focused chart shows",
+            Action::CycleStats => {
+                "show min, average, max and 95th percentile of the focused chart's series"
+            }
+            Action::ZoomOut => "show a longer time range in the charts",
+            Action::ZoomIn => "show a shorter time range in the charts",
+            Action::ExitSearch => "leave search",
+            Action::ClearSearch => "clear the search",
+            Action::DeleteChar => "delete the last searched character",
+            Action::Pause => "freeze the display, collection continues in the background",
+            Action::ScrubBack => "while paused, show an older sample",
+            Action::ScrubForward => "while paused, show a newer sample",
+            Action::TogglePlayback => "play or pause a replayed recording",
+            Action::PlaybackFaster => "replay twice as fast",
+            Action::PlaybackSlower => "replay twice as slow",
+            Action::SeekBack => "jump 10 samples back in a replayed recording",
+            Action::SeekForward => "jump 10 samples forward in a replayed recording",
+            Action::ToggleHelp => "show this help",
+            Action::CloseHelp => "close this help",
+            Action::HelpUp => "scroll this help up",
+            Action::HelpDown => "scroll this help down",
+            Action::SelectPrevious => "highlight the previous host",
+            Action::SelectNext => "highlight the next host",
+            Action::OpenHost => "show the highlighted host in full",
+            Action::CloseHost => "go back to the hosts overview",
+            Action::CloseSeriesList => "close the series list",
+            Action::SeriesPrevious => "highlight the previous series",
+            Action::SeriesNext => "highlight the next series",
+            Action::ToggleSeries => "show or hide the highlighted series",
+            Action::SoloSeries => "show only the highlighted series, or every series again",
+            Action::CycleLegend => "move the legend of the chart to another corner or hide it",
+            Action::CgroupUp => "highlight the previous cgroup",
+            Action::CgroupDown => "highlight the next cgroup",
+            Action::ToggleCgroup => "collapse or expand the highlighted cgroup",
+        }
+    }
+
+    pub fn scope(self) -> Scope {
+        match self {
+            Action::ForceQuit => Scope::Global,
+
+            Action::Quit
+            | Action::Search
+            | Action::SortPid
+            | Action::SortName
+            | Action::SortCpu
+            | Action::SortMemory
+            | Action::SortDiskRead
+            | Action::SortDiskWrite
+            | Action::Pause
+            | Action::ScrubBack
+            | Action::ScrubForward
+            | Action::TogglePlayback
+            | Action::PlaybackFaster
+            | Action::PlaybackSlower
+            | Action::SeekBack
+            | Action::SeekForward
+            | Action::ToggleHelp
+            | Action::CycleCpuView
+            | Action::CycleDiskView
+            | Action::FocusNext
+            | Action::FocusPrevious
+            | Action::CycleChartMarker
+            | Action::CycleChartGraph
+            | Action::ToggleChartFill
+            | Action::ToggleLogScale
+            | Action::OpenSeriesList
+            | Action::CycleStats
+            | Action::ZoomOut
+            | Action::ZoomIn
+            | Action::CloseHost => Scope::Normal,
+
+            Action::SortLoad | Action::SelectPrevious | Action::SelectNext | Action::OpenHost => {
+                Scope::Overview
+            }
+
+            Action::ExitSearch | Action::ClearSearch | Action::DeleteChar => Scope::Search,
+
+            Action::CloseSeriesList
+            | Action::SeriesPrevious
+            | Action::SeriesNext
+            | Action::ToggleSeries
+            | Action::SoloSeries
+            | Action::CycleLegend => Scope::Series,
+
+            Action::CloseHelp | Action::HelpUp | Action::HelpDown => Scope::Help,
+
+            Action::CgroupUp | Action::CgroupDown | Action::ToggleCgroup => Scope::Cgroups,
+        }
+    }
+
+    fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            Action::ForceQuit => &["ctrl+c"],
+            Action::Quit => &["q"],
+            Action::Search => &["/"],
+            Action::SortPid => &["p", "P"],
+            Action::SortName => &["n", "N"],
+            Action::SortCpu => &["c", "C"],
+            Action::SortMemory => &["m", "M"],
+            Action::SortDiskRead => &["r", "R"],
+            Action::SortDiskWrite => &["w", "W"],
+            Action::SortLoad => &["l", "L"],
+            Action::CycleCpuView => &["v"],
+            Action::CycleDiskView => &["d"],
+            Action::FocusNext => &["tab"],
+            Action::FocusPrevious => &["backtab"],
+            Action::CycleChartMarker => &["b"],
+            Action::CycleChartGraph => &["g"],
+            Action::ToggleChartFill => &["a"],
+            Action::ToggleLogScale => &["o"],
+            Action::OpenSeriesList => &["s"],
+            Action::CycleStats => &["t"],
+            Action::ZoomOut => &["z"],
+            Action::ZoomIn => &["Z"],
+            Action::ExitSearch => &["esc"],
+            // ctrl + backspace sends ctrl + w for some reason
+            Action::ClearSearch => &["ctrl+w"],
+            Action::DeleteChar => &["backspace"],
+            Action::Pause => &["space"],
+            Action::ScrubBack => &["left"],
+            Action::ScrubForward => &["right"],
+            Action::TogglePlayback => &["."],
+            Action::PlaybackFaster => &["+", "="],
+            Action::PlaybackSlower => &["-"],
+            Action::SeekBack => &["["],
+            Action::SeekForward => &["]"],
+            Action::ToggleHelp => &["?", "f1"],
+            Action::CloseHelp => &["esc", "?", "q", "f1"],
+            Action::HelpUp => &["up", "k"],
+            Action::HelpDown => &["down", "j"],
+            Action::SelectPrevious => &["up", "k"],
+            Action::SelectNext => &["down", "j"],
+            Action::OpenHost => &["enter"],
+            Action::CloseHost => &["esc"],
+            Action::CloseSeriesList => &["esc", "s", "q"],
+            Action::SeriesPrevious => &["up", "k"],
+            Action::SeriesNext => &["down", "j"],
+            Action::ToggleSeries => &["space", "enter"],
+            Action::SoloSeries => &["o"],
+            Action::CycleLegend => &["l"],
+            Action::CgroupUp => &["up", "k"],
+            Action::CgroupDown => &["down", "j"],
+            Action::ToggleCgroup => &["enter"],
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// A key together with the modifiers that have to be held while pressing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Brings the chord into a canonical form, so that e.g. `shift+a`, `A` and
+    /// a terminal reporting `A` with the shift modifier all compare equal.
+    fn normalized(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        modifiers &= KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+
+        let code = match code {
+            KeyCode::Char(c) => {
+                let c = if modifiers.contains(KeyModifiers::SHIFT) {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                };
+                modifiers.remove(KeyModifiers::SHIFT);
+
+                if modifiers.is_empty() {
+                    KeyCode::Char(c)
+                } else {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                }
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+
+        Self { code, modifiers }
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        *self == Self::normalized(event.code, event.modifiers)
+    }
+
+    /// Whether pressing this chord would otherwise type a character.
+    pub fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (modifiers, key) = if s.len() > 1 && s.ends_with("++") {
+            (&s[..s.len() - 2], "+")
+        } else {
+            match s.rsplit_once('+') {
+                Some((modifiers, key)) if !key.is_empty() => (modifiers, key),
+                _ => ("", s),
+            }
+        };
+
+        let mut parsed_modifiers = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            parsed_modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier \"{modifier}\" in key \"{s}\""),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "space" => KeyCode::Char(' '),
+                lower => match lower.strip_prefix('f').map(str::parse::<u8>) {
+                    Some(Ok(n)) if (1..=24).contains(&n) => KeyCode::F(n),
+                    _ => bail!("unknown key \"{key}\" in \"{s}\""),
+                },
+            },
+        };
+
+        Ok(Self::normalized(code, parsed_modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("backtab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Insert => f.write_str("insert"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// A single key or a list of keys, as written in the `[keys]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(super) enum RawChords {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RawChords {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            RawChords::One(chord) => vec![chord],
+            RawChords::Many(chords) => chords,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: BTreeMap<Action, Vec<KeyChord>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| {
+                let chords = action
+                    .default_chords()
+                    .iter()
+                    .map(|c| c.parse().expect("default key bindings should parse"))
+                    .collect();
+
+                (action, chords)
+            })
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+impl KeyBindings {
+    /// Builds the bindings from the `[keys]` config section. Actions missing from
+    /// the section keep their default keys.
+    pub(super) fn from_raw(raw: BTreeMap<String, RawChords>) -> Result<Self> {
+        let mut keys = Self::default();
+
+        for (name, chords) in raw {
+            let action = Action::from_name(&name)
+                .ok_or_else(|| anyhow!("unknown action \"{name}\" in the keys section"))?;
+
+            let chords = chords
+                .into_vec()
+                .iter()
+                .map(|c| c.parse())
+                .collect::<Result<Vec<_>>>()?;
+
+            keys.bindings.insert(action, chords);
+        }
+
+        keys.check_conflicts()?;
+
+        Ok(keys)
+    }
+
+    pub(super) fn to_raw(&self) -> BTreeMap<String, RawChords> {
+        self.bindings
+            .iter()
+            .map(|(action, chords)| {
+                let chords = chords.iter().map(ToString::to_string).collect();
+                (action.name().to_string(), RawChords::Many(chords))
+            })
+            .collect()
+    }
+
+    fn check_conflicts(&self) -> Result<()> {
+        for (i, (&a1, chords1)) in self.bindings.iter().enumerate() {
+            if a1.scope() == Scope::Search {
+                if let Some(chord) = chords1.iter().find(|c| c.is_plain_char()) {
+                    bail!(
+                        "key \"{chord}\" can't be bound to \"{}\", because it is needed for typing the search",
+                        a1.name()
+                    );
+                }
+            }
+
+            for (&a2, chords2) in self.bindings.iter().skip(i + 1) {
+                if !a1.scope().overlaps(a2.scope()) {
+                    continue;
+                }
+
+                if let Some(chord) = chords1.iter().find(|c| chords2.contains(c)) {
+                    bail!(
+                        "key \"{chord}\" is bound to both \"{}\" and \"{}\"",
+                        a1.name(),
+                        a2.name()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the action bound to the given key in the given input mode.
+    pub fn action(&self, scope: Scope, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(a, _)| a.scope().overlaps(scope))
+            .find(|(_, chords)| chords.iter().any(|c| c.matches(event)))
+            .map(|(&a, _)| a)
+    }
+
+    pub fn chords(&self, action: Action) -> &[KeyChord] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Binds the action to the chords instead of the ones it had.
+    #[cfg(test)]
+    pub(crate) fn rebind(&mut self, action: Action, chords: &[&str]) {
+        let chords = chords.iter().map(|c| c.parse().unwrap()).collect();
+        self.bindings.insert(action, chords);
+    }
+
+    /// A short human readable description of the keys bound to the action.
+    pub fn hint(&self, action: Action) -> String {
+        let chords = self.chords(action);
+
+        if chords.is_empty() {
+            return "unbound".into();
+        }
+
+        chords
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(s: &str) -> KeyChord {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn chord_parsing() {
+        assert_eq!(
+            chord("ctrl+c"),
+            KeyChord {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL
+            }
+        );
+        assert_eq!(chord("shift+a"), chord("A"));
+        assert_eq!(chord("Ctrl+Shift+X"), chord("ctrl+x"));
+        assert_eq!(chord("+").code, KeyCode::Char('+'));
+        assert_eq!(chord("alt++"), chord("ALT++"));
+        assert_eq!(chord("f5").code, KeyCode::F(5));
+        assert_eq!(chord("space").code, KeyCode::Char(' '));
+        assert!("hyper+a".parse::<KeyChord>().is_err());
+        assert!("f99".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn chord_display_round_trips() {
+        for s in [
+            "ctrl+c", "q", "Q", "alt+left", "f12", "space", "+", "ctrl++",
+        ] {
+            assert_eq!(chord(&chord(s).to_string()), chord(s));
+        }
+    }
+
+    #[test]
+    fn event_matching() {
+        let event = KeyEvent::new(KeyCode::Char('C'), KeyModifiers::SHIFT);
+        assert!(chord("C").matches(&event));
+        assert!(!chord("c").matches(&event));
+
+        let event = KeyEvent::new(KeyCode::Char('C'), KeyModifiers::CONTROL);
+        assert!(chord("ctrl+c").matches(&event));
+    }
+
+    #[test]
+    fn conflicts() {
+        let raw = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(a, c)| (a.to_string(), RawChords::One(c.to_string())))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        assert!(KeyBindings::from_raw(raw(&[("quit", "x")])).is_ok());
+        assert!(KeyBindings::from_raw(raw(&[("quit", "c")])).is_err());
+        assert!(KeyBindings::from_raw(raw(&[("quit", "backspace")])).is_ok());
+        assert!(KeyBindings::from_raw(raw(&[("force_quit", "backspace")])).is_err());
+        assert!(KeyBindings::from_raw(raw(&[("select_next", "q")])).is_err());
+        assert!(KeyBindings::from_raw(raw(&[("clear_search", "x")])).is_err());
+        assert!(KeyBindings::from_raw(raw(&[("no_such_action", "x")])).is_err());
+    }
+
+    #[test]
+    fn lookup() {
+        let keys = KeyBindings::default();
+        let event = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+
+        assert_eq!(keys.action(Scope::Normal, &event), Some(Action::Quit));
+        assert_eq!(keys.action(Scope::Search, &event), None);
+
+        let event = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            keys.action(Scope::Cgroups, &event),
+            Some(Action::ToggleCgroup)
+        );
+        assert_eq!(keys.action(Scope::Overview, &event), Some(Action::OpenHost));
+    }
+}
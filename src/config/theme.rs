@@ -83,6 +83,7 @@ impl Theme {
                 frame_color: default_colors::CYAN,
                 title_color: default_colors::CYAN,
                 background_color: Some(SerdeColor(tui::style::Color::Rgb(0x00, 0x2b, 0x36))),
+                alert_color: default_colors::RED,
             },
 
             plot: PlotTheme {
@@ -119,6 +120,9 @@ pub struct WidgetTheme {
     pub title_color: SerdeColor,
     #[serde(default)]
     pub background_color: Option<SerdeColor>,
+    /// Frame color of panes with an active alert.
+    #[serde(default = "default_colors::red")]
+    pub alert_color: SerdeColor,
 }
 
 impl Default for WidgetTheme {
@@ -127,6 +131,7 @@ impl Default for WidgetTheme {
             frame_color: SerdeColor(Color::Cyan),
             title_color: SerdeColor(Color::Cyan),
             background_color: None,
+            alert_color: SerdeColor(Color::Red),
         }
     }
 }
@@ -0,0 +1,250 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    collector::{Snapshot, SECTOR_SIZE},
+    config::CsvLogConfig,
+};
+
+/// A csv file with a fixed header, rotated once it grows past a size limit.
+struct RotatingFile {
+    path: PathBuf,
+    header: String,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Opens the file for appending. A file starting with a different header is rotated
+    /// away first, so that every file only ever contains rows matching its header.
+    fn open(path: &Path, header: String, max_size: u64, max_files: usize) -> Result<Self> {
+        let existing_header = File::open(path).ok().and_then(|f| {
+            let mut line = String::new();
+            BufReader::new(f).read_line(&mut line).ok()?;
+            Some(line.trim_end().to_string()).filter(|l| !l.is_empty())
+        });
+
+        let mut file = Self {
+            path: path.to_path_buf(),
+            header,
+            max_size,
+            max_files,
+            file: open_append(path)?,
+            size: 0,
+        };
+
+        match existing_header {
+            Some(existing) if existing == file.header => {
+                file.size = file.file.metadata()?.len();
+            }
+            Some(_) => file.rotate()?,
+            None => file.write_header()?,
+        }
+
+        Ok(file)
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let header = format!("{}\n", self.header);
+        self.file.write_all(header.as_bytes())?;
+        self.size = header.len() as u64;
+
+        Ok(())
+    }
+
+    /// Moves `path` to `path.1`, `path.1` to `path.2` and so on, dropping the oldest file.
+    fn rotate(&mut self) -> Result<()> {
+        let numbered = |i: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{i}"));
+            PathBuf::from(path)
+        };
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(numbered(self.max_files));
+            for i in (1..self.max_files).rev() {
+                let _ = fs::rename(numbered(i), numbered(i + 1));
+            }
+            fs::rename(&self.path, numbered(1))?;
+        }
+
+        self.file = open_append(&self.path)?;
+        self.write_header()
+    }
+
+    fn write_row(&mut self, row: &str) -> Result<()> {
+        let row = format!("{row}\n");
+        self.file.write_all(row.as_bytes())?;
+        self.size += row.len() as u64;
+
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.to_string_lossy()))
+}
+
+/// Quotes a csv field if needed.
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Appends a row for every snapshot to a csv file, and optionally the processes using
+/// the most cpu to a second one.
+pub struct CsvLogger {
+    config: CsvLogConfig,
+    /// Created on the first snapshot, as the header depends on the cpus and disks.
+    metrics: Option<(RotatingFile, Vec<String>)>,
+    processes: Option<RotatingFile>,
+}
+
+impl CsvLogger {
+    pub fn new(config: CsvLogConfig) -> Result<Self> {
+        let processes = config
+            .processes_path
+            .as_deref()
+            .map(|path| {
+                RotatingFile::open(
+                    path,
+                    "timestamp,pid,name,cpu,mem,disk_read,disk_write".into(),
+                    config.max_size,
+                    config.max_files,
+                )
+            })
+            .transpose()?;
+
+        Ok(Self {
+            config,
+            metrics: None,
+            processes,
+        })
+    }
+
+    pub fn log(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let timestamp = format!("{:.3}", snapshot.timestamp.as_secs_f64());
+
+        if self.metrics.is_none() {
+            let disks = snapshot.disks.keys().cloned().collect::<Vec<_>>();
+
+            let header = std::iter::once("timestamp".to_string())
+                .chain((0..snapshot.cpus.len()).map(|i| format!("cpu{i}")))
+                .chain(std::iter::once("mem_used".to_string()))
+                .chain(
+                    disks
+                        .iter()
+                        .flat_map(|d| [format!("{d}_read"), format!("{d}_write")]),
+                )
+                .map(|h| field(&h))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let file = RotatingFile::open(
+                &self.config.path,
+                header,
+                self.config.max_size,
+                self.config.max_files,
+            )?;
+            self.metrics = Some((file, disks));
+        }
+
+        let (file, disks) = self.metrics.as_mut().unwrap();
+
+        // the header is fixed, so disks that appear later are left out and missing ones are empty
+        let row = std::iter::once(timestamp.clone())
+            .chain(snapshot.cpus.iter().map(|c| format!("{c:.1}")))
+            .chain(std::iter::once(snapshot.mem_used.to_string()))
+            .chain(disks.iter().flat_map(|d| match snapshot.disks.get(d) {
+                Some(info) => [
+                    (info.r_sectors * SECTOR_SIZE).to_string(),
+                    (info.w_sectors * SECTOR_SIZE).to_string(),
+                ],
+                None => [String::new(), String::new()],
+            }))
+            .collect::<Vec<_>>()
+            .join(",");
+        file.write_row(&row)?;
+
+        if let Some(file) = &mut self.processes {
+            let mut processes = snapshot.processes.iter().collect::<Vec<_>>();
+            processes.sort_by(|p1, p2| p2.cpu.total_cmp(&p1.cpu));
+
+            for p in processes.into_iter().take(self.config.top) {
+                file.write_row(&format!(
+                    "{timestamp},{},{},{:.1},{},{},{}",
+                    p.pid,
+                    field(&p.name),
+                    p.cpu,
+                    p.mem,
+                    p.disk_r,
+                    p.disk_w
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let dir = std::env::temp_dir().join(format!("jwtop-csv-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("log.csv");
+
+        let mut file = RotatingFile::open(&path, "a,b".into(), 20, 2).unwrap();
+        for i in 0..10 {
+            file.write_row(&format!("{i},{i}")).unwrap();
+        }
+
+        let read = |p: &Path| fs::read_to_string(p).unwrap();
+        assert!(read(&path).starts_with("a,b\n"));
+        assert!(read(&dir.join("log.csv.1")).starts_with("a,b\n"));
+        assert!(read(&dir.join("log.csv.2")).starts_with("a,b\n"));
+        assert!(!dir.join("log.csv.3").exists());
+        assert!(read(&path).ends_with("9,9\n"));
+        assert!(read(&dir.join("log.csv.1")).ends_with("7,7\n"));
+        assert!(read(&dir.join("log.csv.2")).ends_with("3,3\n"));
+
+        // reopening with a different header moves the old file out of the way
+        fs::write(&path, "a,b\n1,1\n").unwrap();
+        RotatingFile::open(&path, "a,b,c".into(), 20, 2).unwrap();
+        assert_eq!(read(&path), "a,b,c\n");
+        assert_eq!(read(&dir.join("log.csv.1")), "a,b\n1,1\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(field("plain"), "plain");
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
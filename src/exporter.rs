@@ -0,0 +1,267 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+
+use crate::collector::{Snapshot, SECTOR_SIZE};
+
+/// Everything exposed on the metrics endpoint.
+#[derive(Debug, Default)]
+struct Metrics {
+    snapshot: Snapshot,
+    /// Bytes read and written by every disk since the exporter started.
+    disk_totals: BTreeMap<String, (u64, u64)>,
+}
+
+impl Metrics {
+    fn update(&mut self, snapshot: &Snapshot) {
+        for (name, info) in &snapshot.disks {
+            let (read, written) = self.disk_totals.entry(name.clone()).or_default();
+            *read += (info.r_sectors * SECTOR_SIZE) as u64;
+            *written += (info.w_sectors * SECTOR_SIZE) as u64;
+        }
+
+        self.snapshot = snapshot.clone();
+    }
+
+    /// Formats the metrics in the prometheus text exposition format.
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        };
+
+        family(
+            "jwtop_cpu_usage_percent",
+            "gauge",
+            "Usage of a cpu in percent.",
+            self.snapshot
+                .cpus
+                .iter()
+                .enumerate()
+                .map(|(i, usage)| (labels(&[("cpu", &i.to_string())]), usage.to_string()))
+                .collect(),
+        );
+
+        family(
+            "jwtop_memory_used_bytes",
+            "gauge",
+            "Used memory in bytes.",
+            vec![(String::new(), self.snapshot.mem_used.to_string())],
+        );
+
+        family(
+            "jwtop_memory_total_bytes",
+            "gauge",
+            "Total memory in bytes.",
+            vec![(String::new(), self.snapshot.mem_total.to_string())],
+        );
+
+        family(
+            "jwtop_disk_read_bytes_total",
+            "counter",
+            "Bytes read from a disk since jwtop started.",
+            self.disk_totals
+                .iter()
+                .map(|(name, (read, _))| (labels(&[("disk", name)]), read.to_string()))
+                .collect(),
+        );
+
+        family(
+            "jwtop_disk_written_bytes_total",
+            "counter",
+            "Bytes written to a disk since jwtop started.",
+            self.disk_totals
+                .iter()
+                .map(|(name, (_, written))| (labels(&[("disk", name)]), written.to_string()))
+                .collect(),
+        );
+
+        let process_labels =
+            |pid: u32, name: &str| labels(&[("pid", &pid.to_string()), ("name", name)]);
+
+        family(
+            "jwtop_process_cpu_usage_percent",
+            "gauge",
+            "Cpu usage of a process in percent of all cpus.",
+            self.snapshot
+                .processes
+                .iter()
+                .map(|p| (process_labels(p.pid, &p.name), p.cpu.to_string()))
+                .collect(),
+        );
+
+        family(
+            "jwtop_process_memory_bytes",
+            "gauge",
+            "Memory used by a process in bytes.",
+            self.snapshot
+                .processes
+                .iter()
+                .map(|p| (process_labels(p.pid, &p.name), p.mem.to_string()))
+                .collect(),
+        );
+
+        out
+    }
+}
+
+fn labels(labels: &[(&str, &str)]) -> String {
+    let labels = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', r"\\")
+                .replace('"', r#"\""#)
+                .replace('\n', r"\n");
+
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("{{{labels}}}")
+}
+
+/// Serves the latest snapshot on a `/metrics` http endpoint.
+pub struct Exporter {
+    metrics: Arc<Mutex<Metrics>>,
+    addr: SocketAddr,
+}
+
+impl Exporter {
+    /// Starts serving on the given address in a background thread.
+    pub fn serve(addr: &str) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("Failed to listen on {addr}"))?;
+        let addr = listener.local_addr()?;
+
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+
+        {
+            let metrics = metrics.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // a misbehaving client shouldn't take the exporter down
+                    let _ = respond(stream, &metrics);
+                }
+            });
+        }
+
+        Ok(Self { metrics, addr })
+    }
+
+    /// The address the exporter actually listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn update(&self, snapshot: &Snapshot) {
+        self.metrics.lock().unwrap().update(snapshot);
+    }
+}
+
+fn respond(mut stream: TcpStream, metrics: &Mutex<Metrics>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // skip the headers, we don't need any of them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.lock().unwrap().render()),
+        (Some("GET"), Some(_)) => ("404 Not Found", "not found, try /metrics\n".into()),
+        _ => ("405 Method Not Allowed", "only GET is supported\n".into()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::collector::{DiskInfo, ProcessInfo};
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_metrics() {
+        let exporter = Exporter::serve("127.0.0.1:0").unwrap();
+
+        let snapshot = Snapshot {
+            cpus: vec![12.5, 50.0],
+            mem_used: 1024,
+            mem_total: 4096,
+            disks: [(
+                "sda".to_string(),
+                DiskInfo {
+                    r_sectors: 2,
+                    w_sectors: 1,
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            processes: vec![ProcessInfo {
+                pid: 42,
+                cpu: 3.5,
+                mem: 2048,
+                name: "we\"ird".into(),
+                disk_r: 0,
+                disk_w: 0,
+            }],
+            ..Default::default()
+        };
+        exporter.update(&snapshot);
+        exporter.update(&snapshot);
+
+        let response = get(exporter.local_addr(), "/metrics");
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("jwtop_cpu_usage_percent{cpu=\"1\"} 50\n"));
+        assert!(response.contains("jwtop_memory_used_bytes 1024\n"));
+        assert!(response.contains("jwtop_disk_read_bytes_total{disk=\"sda\"} 2048\n"));
+        assert!(response.contains("# TYPE jwtop_disk_written_bytes_total counter\n"));
+        assert!(
+            response.contains("jwtop_process_memory_bytes{pid=\"42\",name=\"we\\\"ird\"} 2048\n")
+        );
+
+        assert!(get(exporter.local_addr(), "/").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -1,9 +1,15 @@
 use crate::{
-    app::{App, AppResult, InputState},
+    app::{App, AppResult, InputState, Pane},
+    cgroups::CgroupColumn,
+    config::Action,
+    hosts::HostColumn,
     ui::processes::Column,
 };
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
+/// How many samples the seek actions jump through a replayed recording.
+const SEEK_STEP: isize = 10;
+
 fn change_processes_sort_into(app: &mut App, selected_column: Column) {
     if let InputState::ProcessesSortSelection { column, direction } = &mut app.input_state {
         if *column == selected_column {
@@ -17,97 +23,250 @@ fn change_processes_sort_into(app: &mut App, selected_column: Column) {
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-                return Ok(());
+    if key_event.kind == KeyEventKind::Release {
+        return Ok(());
+    }
+
+    match app.config.keys.action(app.scope(), &key_event) {
+        Some(action) => perform_action(action, app),
+
+        None => {
+            if let InputState::ProcessesSearch { search, .. } = &mut app.input_state {
+                if let KeyCode::Char(c) = key_event.code {
+                    if !key_event
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                    {
+                        search.push(c);
+                    }
+                }
             }
         }
+    }
+
+    Ok(())
+}
+
+/// Updates the state of [`App`] according to the action a key was bound to.
+pub fn perform_action(action: Action, app: &mut App) {
+    if app.in_overview() && perform_overview_action(action, app) {
+        return;
+    }
 
-        _ => {}
+    if app.focus == Pane::Cgroups && perform_cgroups_action(action, app) {
+        return;
     }
 
-    match &mut app.input_state {
-        InputState::ProcessesSortSelection { column, direction } => match key_event.code {
-            KeyCode::Char('/') => {
+    match action {
+        Action::ForceQuit | Action::Quit => app.quit(),
+
+        Action::Search => {
+            if let InputState::ProcessesSortSelection { column, direction } = &app.input_state {
                 app.input_state = InputState::ProcessesSearch {
                     old_column: Some(*column),
                     old_direction: Some(*direction),
                     search: String::new(),
                 }
             }
+        }
+
+        Action::SortPid => change_processes_sort_into(app, Column::Pid),
+        Action::SortName => change_processes_sort_into(app, Column::Name),
+        Action::SortCpu => change_processes_sort_into(app, Column::Cpu),
+        Action::SortMemory => change_processes_sort_into(app, Column::Memory),
+        Action::SortDiskRead => change_processes_sort_into(app, Column::DiskRead),
+        Action::SortDiskWrite => change_processes_sort_into(app, Column::DiskWrite),
 
-            KeyCode::Char('q') => {
-                app.quit();
+        Action::ExitSearch => {
+            if let InputState::ProcessesSearch {
+                old_column,
+                old_direction,
+                ..
+            } = &app.input_state
+            {
+                let column = old_column.unwrap_or_default();
+                let direction = old_direction.unwrap_or(column.default_sort_direction());
+                app.input_state = InputState::ProcessesSortSelection { column, direction }
             }
+        }
 
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                change_processes_sort_into(app, Column::Cpu);
+        Action::ClearSearch => {
+            if let InputState::ProcessesSearch { search, .. } = &mut app.input_state {
+                search.clear();
             }
+        }
 
-            KeyCode::Char('p') | KeyCode::Char('P') => {
-                change_processes_sort_into(app, Column::Pid);
+        Action::DeleteChar => {
+            if let InputState::ProcessesSearch { search, .. } = &mut app.input_state {
+                search.pop();
             }
+        }
+
+        Action::Pause => app.toggle_pause(),
+        Action::ScrubBack => app.scrub(1),
+        Action::ScrubForward => app.scrub(-1),
 
-            KeyCode::Char('n') | KeyCode::Char('N') => {
-                change_processes_sort_into(app, Column::Name);
+        Action::TogglePlayback => {
+            if let Some(replay) = app.replay_mut() {
+                replay.playing = !replay.playing;
             }
+        }
 
-            KeyCode::Char('m') | KeyCode::Char('M') => {
-                change_processes_sort_into(app, Column::Memory);
+        Action::PlaybackFaster => {
+            if let Some(replay) = app.replay_mut() {
+                replay.faster();
             }
+        }
 
-            KeyCode::Char('r') | KeyCode::Char('R') => {
-                change_processes_sort_into(app, Column::DiskRead);
+        Action::PlaybackSlower => {
+            if let Some(replay) = app.replay_mut() {
+                replay.slower();
             }
+        }
+
+        Action::SeekBack => app.seek(-SEEK_STEP),
+        Action::SeekForward => app.seek(SEEK_STEP),
 
-            KeyCode::Char('w') | KeyCode::Char('W') => {
-                change_processes_sort_into(app, Column::DiskWrite);
+        Action::ToggleHelp => app.help = Some(0),
+        Action::CloseHelp => app.help = None,
+
+        Action::HelpUp => {
+            if let Some(offset) = &mut app.help {
+                *offset = offset.saturating_sub(1);
             }
+        }
 
-            // Other handlers you could add here.
-            _ => {}
-        },
+        Action::HelpDown => {
+            if let Some(offset) = &mut app.help {
+                *offset = offset.saturating_add(1);
+            }
+        }
 
-        InputState::ProcessesSearch {
-            old_column,
-            old_direction,
-            search,
-        } => {
-            match key_event.code {
-                KeyCode::Esc => {
-                    let column = old_column.unwrap_or_default();
-                    let direction = old_direction.unwrap_or(column.default_sort_direction());
-                    app.input_state = InputState::ProcessesSortSelection { column, direction }
-                }
+        Action::CycleCpuView => app.cpu_view = app.cpu_view.next(),
+        Action::CycleDiskView => app.disk_view = app.disk_view.next(),
 
-                // ctrl + backspace sends ctrl + w for some reason
-                KeyCode::Char('w') | KeyCode::Char('W')
-                    if key_event.modifiers.contains(KeyModifiers::CONTROL) =>
-                {
-                    search.clear();
-                }
+        Action::FocusNext => app.cycle_focus(true),
+        Action::FocusPrevious => app.cycle_focus(false),
 
-                KeyCode::Char(c)
-                    if key_event.kind == KeyEventKind::Press
-                        || key_event.kind == KeyEventKind::Repeat =>
-                {
-                    search.push(c);
-                }
+        Action::CycleChartMarker => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.marker = options.marker.next();
+            }
+        }
 
-                KeyCode::Backspace
-                    if key_event.kind == KeyEventKind::Press
-                        || key_event.kind == KeyEventKind::Repeat =>
-                {
-                    search.pop();
-                }
+        Action::CycleChartGraph => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.graph = options.graph.next();
+            }
+        }
+
+        Action::ToggleChartFill => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.fill = !options.fill;
+            }
+        }
+
+        Action::ToggleLogScale => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.log_scale = !options.log_scale;
+            }
+        }
 
-                _ => {}
+        Action::CloseHost => app.close_host(),
+
+        Action::ZoomOut => app.zoom_by(1),
+        Action::ZoomIn => app.zoom_by(-1),
+
+        Action::OpenSeriesList => app.open_series_list(),
+
+        Action::CycleStats => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.stats = options.stats.next();
+            }
+        }
+
+        Action::CloseSeriesList => app.series_list = None,
+        Action::SeriesPrevious => app.select_series(-1),
+        Action::SeriesNext => app.select_series(1),
+        Action::ToggleSeries => app.toggle_series(),
+        Action::SoloSeries => app.solo_series(),
+
+        Action::CycleLegend => {
+            if let Some(options) = app.focused_chart_mut() {
+                options.legend = options.legend.next();
             }
         }
+
+        // only available in the hosts overview
+        Action::SortLoad | Action::SelectPrevious | Action::SelectNext | Action::OpenHost => {}
+
+        // only available in the cgroups pane
+        Action::CgroupUp | Action::CgroupDown | Action::ToggleCgroup => {}
     }
+}
 
-    Ok(())
+/// Performs the actions that apply to the cgroup tree while its pane is focused.
+/// Returns whether the action was handled.
+fn perform_cgroups_action(action: Action, app: &mut App) -> bool {
+    let groups = app.displayed_history().cgroups.clone();
+    let cgroups = &mut app.cgroups;
+
+    match action {
+        Action::SortPid => cgroups.sort_by(CgroupColumn::Pids),
+        Action::SortName => cgroups.sort_by(CgroupColumn::Name),
+        Action::SortCpu => cgroups.sort_by(CgroupColumn::Cpu),
+        Action::SortMemory => cgroups.sort_by(CgroupColumn::Memory),
+        Action::SortDiskRead => cgroups.sort_by(CgroupColumn::IoRead),
+        Action::SortDiskWrite => cgroups.sort_by(CgroupColumn::IoWrite),
+
+        Action::CgroupUp => cgroups.select(&groups, -1),
+        Action::CgroupDown => cgroups.select(&groups, 1),
+        Action::ToggleCgroup => cgroups.toggle(&groups),
+
+        _ => return false,
+    }
+
+    true
+}
+
+/// Performs the actions that behave differently in the hosts overview.
+/// Returns whether the action was handled.
+fn perform_overview_action(action: Action, app: &mut App) -> bool {
+    let Some(hosts) = app.hosts_mut() else {
+        return false;
+    };
+
+    match action {
+        Action::SortName => hosts.sort_by(HostColumn::Name),
+        Action::SortCpu => hosts.sort_by(HostColumn::Cpu),
+        Action::SortMemory => hosts.sort_by(HostColumn::Memory),
+        Action::SortLoad => hosts.sort_by(HostColumn::Load),
+        Action::SortDiskRead => hosts.sort_by(HostColumn::DiskRead),
+        Action::SortDiskWrite => hosts.sort_by(HostColumn::DiskWrite),
+
+        Action::SelectPrevious => hosts.select(-1),
+        Action::SelectNext => hosts.select(1),
+        Action::OpenHost => app.open_host(),
+
+        // the overview has no processes table and no charts
+        Action::Search
+        | Action::SortPid
+        | Action::Pause
+        | Action::ScrubBack
+        | Action::ScrubForward
+        | Action::FocusNext
+        | Action::FocusPrevious
+        | Action::CycleChartMarker
+        | Action::CycleChartGraph
+        | Action::ToggleChartFill
+        | Action::ToggleLogScale
+        | Action::OpenSeriesList
+        | Action::CycleStats
+        | Action::ZoomOut
+        | Action::ZoomIn => {}
+
+        _ => return false,
+    }
+
+    true
 }
@@ -0,0 +1,144 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    collector::{Collector, ProcessInfo, Snapshot, SECTOR_SIZE},
+    exporter::Exporter,
+};
+
+#[derive(Debug, Serialize)]
+struct Memory {
+    used: u64,
+    total: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Disk {
+    read_bytes: u64,
+    written_bytes: u64,
+}
+
+/// The json representation of a single sample.
+#[derive(Debug, Serialize)]
+struct Sample<'a> {
+    /// Seconds since the unix epoch.
+    timestamp: f64,
+    cpus: &'a [f64],
+    memory: Memory,
+    disks: BTreeMap<&'a str, Disk>,
+    processes: Vec<&'a ProcessInfo>,
+}
+
+impl<'a> Sample<'a> {
+    fn new(snapshot: &'a Snapshot, top: usize) -> Self {
+        let mut processes = snapshot.processes.iter().collect::<Vec<_>>();
+        processes.sort_by(|p1, p2| p2.cpu.total_cmp(&p1.cpu));
+        processes.truncate(top);
+
+        Self {
+            timestamp: snapshot.timestamp.as_secs_f64(),
+            cpus: &snapshot.cpus,
+            memory: Memory {
+                used: snapshot.mem_used,
+                total: snapshot.mem_total,
+            },
+            disks: snapshot
+                .disks
+                .iter()
+                .map(|(name, info)| {
+                    let disk = Disk {
+                        read_bytes: (info.r_sectors * SECTOR_SIZE) as u64,
+                        written_bytes: (info.w_sectors * SECTOR_SIZE) as u64,
+                    };
+
+                    (name.as_str(), disk)
+                })
+                .collect(),
+            processes,
+        }
+    }
+}
+
+/// Options for running without a terminal interface.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Stop after printing this many samples.
+    pub count: Option<u64>,
+    pub interval: Duration,
+    /// How many of the processes using the most cpu to print.
+    pub top: usize,
+}
+
+/// Samples the system every interval and prints every sample as a line of json.
+pub fn run(
+    mut collector: Collector,
+    options: Options,
+    exporter: Option<Exporter>,
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut printed = 0;
+
+    while options.count.is_none_or(|count| printed < count) {
+        thread::sleep(options.interval);
+
+        let snapshot = collector.sample();
+
+        if let Some(exporter) = &exporter {
+            exporter.update(&snapshot);
+        }
+        let result = serde_json::to_writer(&mut *out, &Sample::new(&snapshot, options.top))
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(out))
+            .and_then(|()| out.flush());
+
+        match result {
+            Ok(()) => {}
+            // the reader went away, e.g. `jwtop --json | head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+
+        printed += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prints_a_line_per_sample() {
+        let options = Options {
+            count: Some(2),
+            interval: Duration::from_millis(10),
+            top: 1,
+        };
+
+        let mut out = Vec::new();
+        run(Collector::new(), options, None, &mut out).unwrap();
+
+        let lines = String::from_utf8(out).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+
+        for line in lines {
+            let sample = serde_json::from_str::<serde_json::Value>(line).unwrap();
+            let sample = sample.as_object().unwrap();
+
+            assert_eq!(
+                sample.keys().collect::<Vec<_>>(),
+                ["cpus", "disks", "memory", "processes", "timestamp"]
+            );
+            assert!(sample["processes"].as_array().unwrap().len() <= 1);
+        }
+    }
+}
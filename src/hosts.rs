@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+
+use crate::{
+    app::{History, HISTORY_LEN},
+    collector::DiskInfo,
+    remote::{Address, RemoteFeed},
+    ui::processes::SortDirection,
+};
+
+/// A remote host shown in the overview.
+pub struct Host {
+    pub name: String,
+    pub feed: RemoteFeed,
+    pub history: History,
+    /// Whether any snapshot was received from the host yet.
+    pub connected: bool,
+}
+
+impl Host {
+    /// Average usage of all cpus in percent, over the given history index.
+    fn cpu_at(&self, index: usize) -> f64 {
+        let cpus = &self.history.cpus;
+        if cpus.is_empty() {
+            return 0.0;
+        }
+
+        cpus.iter().map(|c| c[index]).sum::<f64>() / cpus.len() as f64
+    }
+
+    pub fn cpu(&self) -> f64 {
+        self.cpu_at(HISTORY_LEN - 1)
+    }
+
+    /// Average cpu usage of every sample in the history, oldest first.
+    pub fn cpu_history(&self) -> Vec<f64> {
+        (0..HISTORY_LEN).map(|i| self.cpu_at(i)).collect()
+    }
+
+    pub fn mem(&self) -> f64 {
+        if self.history.mem_total == 0 {
+            return 0.0;
+        }
+
+        self.history.mem.back().copied().unwrap_or_default() / self.history.mem_total as f64 * 100.0
+    }
+
+    pub fn load(&self) -> f64 {
+        self.history.load_average[0]
+    }
+
+    /// Name of the process using the most cpu.
+    pub fn top_process(&self) -> Option<&str> {
+        self.history
+            .processes
+            .back()?
+            .iter()
+            .max_by(|p1, p2| p1.cpu.total_cmp(&p2.cpu))
+            .map(|p| p.name.as_str())
+    }
+
+    /// Bytes read and written per second by all disks, as of the newest sample.
+    pub fn disk_io(&self) -> (f64, f64) {
+        self.history
+            .disks
+            .values()
+            .filter_map(|d| d.back())
+            .map(DiskInfo::bytes_per_second)
+            .fold((0.0, 0.0), |(r, w), [read, written]| {
+                (r + read, w + written)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HostColumn {
+    Name,
+    #[default]
+    Cpu,
+    Memory,
+    Load,
+    DiskRead,
+    DiskWrite,
+}
+
+impl HostColumn {
+    pub fn default_sort_direction(&self) -> SortDirection {
+        match self {
+            HostColumn::Name => SortDirection::Ascending,
+            _ => SortDirection::Descending,
+        }
+    }
+
+    fn compare_by(&self, h1: &Host, h2: &Host) -> Ordering {
+        match self {
+            HostColumn::Name => h1.name.cmp(&h2.name),
+            HostColumn::Cpu => h1.cpu().total_cmp(&h2.cpu()),
+            HostColumn::Memory => h1.mem().total_cmp(&h2.mem()),
+            HostColumn::Load => h1.load().total_cmp(&h2.load()),
+            HostColumn::DiskRead => h1.disk_io().0.total_cmp(&h2.disk_io().0),
+            HostColumn::DiskWrite => h1.disk_io().1.total_cmp(&h2.disk_io().1),
+        }
+    }
+}
+
+/// Every host from the `[hosts]` config section.
+pub struct Hosts {
+    pub hosts: Vec<Host>,
+    /// Index into `hosts` of the highlighted row.
+    pub selected: usize,
+    pub column: HostColumn,
+    pub direction: SortDirection,
+    /// Index into `hosts` of the host shown in the full layout, if any.
+    pub opened: Option<usize>,
+}
+
+impl Hosts {
+    pub fn connect(hosts: &[(String, Address)]) -> Self {
+        let hosts = hosts
+            .iter()
+            .map(|(name, address)| Host {
+                name: name.clone(),
+                feed: RemoteFeed::connect(address.clone()),
+                history: History::new(0),
+                connected: false,
+            })
+            .collect();
+
+        let column = HostColumn::default();
+
+        Self {
+            hosts,
+            selected: 0,
+            column,
+            direction: column.default_sort_direction(),
+            opened: None,
+        }
+    }
+
+    pub fn poll(&mut self) {
+        for host in &mut self.hosts {
+            for snapshot in host.feed.poll() {
+                host.connected = true;
+                host.history.push(snapshot);
+            }
+        }
+    }
+
+    /// Indices into `hosts` in the order they're shown.
+    pub fn sorted(&self) -> Vec<usize> {
+        let mut order = (0..self.hosts.len()).collect::<Vec<_>>();
+
+        order.sort_by(|&i1, &i2| {
+            let ordering = self.column.compare_by(&self.hosts[i1], &self.hosts[i2]);
+
+            match self.direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+
+        order
+    }
+
+    pub fn sort_by(&mut self, column: HostColumn) {
+        if self.column == column {
+            self.direction = self.direction.reversed();
+        } else {
+            self.column = column;
+            self.direction = column.default_sort_direction();
+        }
+    }
+
+    /// Moves the highlight `by` rows down, or up if negative.
+    pub fn select(&mut self, by: isize) {
+        let order = self.sorted();
+        let Some(position) = order.iter().position(|&i| i == self.selected) else {
+            return;
+        };
+
+        let position = position
+            .saturating_add_signed(by)
+            .min(order.len().saturating_sub(1));
+        self.selected = order[position];
+    }
+
+    pub fn opened(&self) -> Option<&Host> {
+        self.opened.map(|i| &self.hosts[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, time::Duration};
+
+    use crate::collector::Snapshot;
+
+    use super::*;
+
+    /// Hosts that received a snapshot each with the given cpu usage and sectors read from
+    /// a disk over half a second.
+    fn hosts(samples: &[(&str, f64, usize)]) -> Hosts {
+        let mut hosts = Hosts::connect(&[]);
+
+        for &(name, cpu, r_sectors) in samples {
+            let mut history = History::new(1);
+            history.push(Snapshot {
+                timestamp: Duration::from_secs(1),
+                cpus: vec![cpu],
+                disks: [(
+                    "sda".to_string(),
+                    DiskInfo {
+                        r_sectors,
+                        elapsed_ms: 500,
+                        ..Default::default()
+                    },
+                )]
+                .into(),
+                ..Default::default()
+            });
+
+            hosts.hosts.push(Host {
+                name: name.to_string(),
+                // nothing listens on port 1, and nothing is polled from it
+                feed: RemoteFeed::connect(Address::Tcp("127.0.0.1:1".into())),
+                history,
+                connected: true,
+            });
+        }
+
+        hosts
+    }
+
+    #[test]
+    fn disk_rates() {
+        let mut hosts = hosts(&[("a", 0.0, 2000)]);
+        let host = &mut hosts.hosts[0];
+        assert_eq!(host.disk_io(), (2000.0 * 512.0 * 2.0, 0.0));
+
+        // every disk counts, each over its own time
+        host.history.push(Snapshot {
+            timestamp: Duration::from_secs(2),
+            disks: [
+                (
+                    "sda".to_string(),
+                    DiskInfo {
+                        r_sectors: 1000,
+                        w_sectors: 10,
+                        elapsed_ms: 1000,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "sdb".to_string(),
+                    DiskInfo {
+                        w_sectors: 20,
+                        elapsed_ms: 2000,
+                        ..Default::default()
+                    },
+                ),
+                ("sdc".to_string(), DiskInfo::default()),
+            ]
+            .into(),
+            ..Default::default()
+        });
+        assert_eq!(
+            host.disk_io(),
+            (1000.0 * 512.0, 10.0 * 512.0 + 10.0 * 512.0)
+        );
+    }
+
+    #[test]
+    fn sorting_and_selection() {
+        let mut hosts = hosts(&[("b", 50.0, 10), ("a", 10.0, 30), ("c", 90.0, 20)]);
+
+        // busiest first by default
+        assert_eq!(hosts.sorted(), [2, 0, 1]);
+
+        hosts.sort_by(HostColumn::Name);
+        assert_eq!(hosts.sorted(), [1, 0, 2]);
+        hosts.sort_by(HostColumn::Name);
+        assert_eq!(hosts.sorted(), [2, 0, 1]);
+
+        hosts.sort_by(HostColumn::DiskRead);
+        assert_eq!(hosts.direction, SortDirection::Descending);
+        assert_eq!(hosts.sorted(), [1, 2, 0]);
+
+        // the highlight moves through the shown order and stops at its ends
+        hosts.selected = 1;
+        hosts.select(1);
+        assert_eq!(hosts.selected, 2);
+        hosts.select(5);
+        assert_eq!(hosts.selected, 0);
+        hosts.select(-1);
+        assert_eq!(hosts.selected, 2);
+        hosts.select(-5);
+        assert_eq!(hosts.selected, 1);
+    }
+
+    #[test]
+    fn unreachable_host() {
+        // a port that was free a moment ago, so connecting is refused
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let mut hosts = Hosts::connect(&[("down".to_string(), Address::Tcp(address))]);
+
+        for _ in 0..250 {
+            hosts.poll();
+            if hosts.hosts[0].feed.error.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let host = &hosts.hosts[0];
+        assert!(host.feed.error.is_some());
+        assert!(!host.connected);
+        assert_eq!(host.cpu(), 0.0);
+        assert_eq!(host.disk_io(), (0.0, 0.0));
+        assert_eq!(hosts.sorted(), [0]);
+    }
+}
@@ -1,6 +1,9 @@
 /// Application.
 pub mod app;
 
+/// Metrics sampling.
+pub mod collector;
+
 /// Terminal events handler.
 pub mod event;
 
@@ -15,3 +18,33 @@ pub mod handler;
 
 /// The app config
 pub mod config;
+
+/// Recording snapshots to files and playing them back.
+pub mod recording;
+
+/// Printing samples as json without a terminal interface.
+pub mod headless;
+
+/// Prometheus metrics endpoint.
+pub mod exporter;
+
+/// Logging samples to csv files.
+pub mod csv_log;
+
+/// Streaming snapshots between an agent and clients over sockets.
+pub mod remote;
+
+/// Many remote hosts at once.
+pub mod hosts;
+
+/// Threshold alerts over the sampled metrics.
+pub mod alerts;
+
+/// Interface settings kept between runs.
+pub mod state;
+
+/// Long-term history at decreasing resolutions.
+pub mod tiers;
+
+/// The cgroup tree shown in the cgroups pane.
+pub mod cgroups;
@@ -1,10 +1,15 @@
 use clap::Parser;
-use jwtop::app::{App, AppResult};
+use jwtop::app::{App, AppResult, Collector, Source};
 use jwtop::event::{Event, EventHandler};
 use jwtop::handler::handle_key_events;
+use jwtop::hosts::Hosts;
+use jwtop::recording::{Recorder, Replay};
+use jwtop::remote::RemoteFeed;
+use jwtop::state::State;
 use jwtop::tui::Tui;
 use std::io;
-use tui::backend::CrosstermBackend;
+use std::time::Duration;
+use tui::backend::{Backend, CrosstermBackend};
 use tui::Terminal;
 
 const TICK_RATE: u64 = 1000;
@@ -26,10 +31,72 @@ fn main() -> AppResult<()> {
         return Ok(());
     }
 
+    let exporter = cli
+        .serve_metrics
+        .as_deref()
+        .map(jwtop::exporter::Exporter::serve)
+        .transpose()?;
+
     let config = jwtop::config::Config::load(&cli)?;
 
+    let mut collector =
+        Collector::new().exclude_filesystem_types(config.filesystems.exclude_types.clone());
+    if let Some(root) = &config.cpufreq_root {
+        collector = collector.cpufreq_root(root.clone());
+    }
+    if let Some(root) = &config.power_supply_root {
+        collector = collector.power_supply_root(root.clone());
+    }
+    if let Some(root) = &config.cgroup_root {
+        collector = collector.cgroup_root(root.clone());
+    }
+
+    if let Some(address) = &cli.agent {
+        jwtop::remote::run_agent(
+            collector,
+            address,
+            Duration::from_millis(cli.interval),
+            exporter,
+        )?;
+        return Ok(());
+    }
+
+    if cli.json {
+        let options = jwtop::headless::Options {
+            count: cli.count,
+            interval: Duration::from_millis(cli.interval),
+            top: cli.top,
+        };
+
+        jwtop::headless::run(collector, options, exporter, &mut io::stdout().lock())?;
+
+        return Ok(());
+    }
+
+    let source = match (&cli.replay, &cli.connect) {
+        _ if cli.overview => {
+            if config.hosts.is_empty() {
+                return Err("No hosts to show, add them to the [hosts] config section".into());
+            }
+            Source::Hosts(Hosts::connect(&config.hosts))
+        }
+        (Some(path), _) => Source::Replay(Replay::load(path)?),
+        (None, Some(address)) => Source::Remote(RemoteFeed::connect(address.clone())),
+        (None, None) => Source::Live(Box::new(collector)),
+    };
+
+    let recorder = cli.record.as_deref().map(Recorder::create).transpose()?;
+
     // Create an application.
-    let mut app = App::new(config);
+    let mut app = App::new(config, source, recorder)?;
+
+    if let Some(exporter) = exporter {
+        app.export(exporter);
+    }
+
+    if let Some(path) = State::default_path() {
+        app.restore(State::load(&path)?);
+    }
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
@@ -38,20 +105,30 @@ fn main() -> AppResult<()> {
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
-    // Start the main loop.
+    let result = run(&mut tui, &mut app);
+
+    // Exit the user interface, also when the main loop failed, e.g. writing the history
+    // to a full disk.
+    tui.exit()?;
+    result?;
+
+    app.state.save()?;
+    Ok(())
+}
+
+/// The main loop, until the application quits or fails.
+fn run<B: Backend>(tui: &mut Tui<B>, app: &mut App) -> AppResult<()> {
     while app.running {
         // Render the user interface.
-        tui.draw(&mut app)?;
+        tui.draw(app)?;
         // Handle events.
         match tui.events.next()? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
+            Event::Tick => app.tick()?,
+            Event::Key(key_event) => handle_key_events(key_event, app)?,
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
         }
     }
 
-    // Exit the user interface.
-    tui.exit()?;
     Ok(())
 }
@@ -0,0 +1,355 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::collector::Snapshot;
+
+const MAGIC: &[u8; 8] = b"JWTOPREC";
+
+/// Bumped whenever the layout of [`Snapshot`] changes.
+pub const VERSION: u16 = 1;
+
+/// Frames claiming to be longer than this are treated as corrupted, rather than allocated.
+const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+/// Writes the header identifying a stream of snapshot frames.
+pub fn write_header(writer: &mut impl Write) -> io::Result<()> {
+    write_versioned_header(writer, MAGIC, VERSION)
+}
+
+/// Reads and validates the header written by [`write_header`].
+pub fn read_header(reader: &mut impl Read) -> Result<()> {
+    read_versioned_header(reader, MAGIC, VERSION, "recording")
+}
+
+/// Writes a header identifying the kind of a file and the version of its layout.
+pub(crate) fn write_versioned_header(
+    writer: &mut impl Write,
+    magic: &[u8; 8],
+    version: u16,
+) -> io::Result<()> {
+    writer.write_all(magic)?;
+    writer.write_all(&version.to_le_bytes())
+}
+
+/// Reads and validates the header written by [`write_versioned_header`], `what` naming the
+/// kind of file in errors.
+pub(crate) fn read_versioned_header(
+    reader: &mut impl Read,
+    magic: &[u8; 8],
+    version: u16,
+    what: &str,
+) -> Result<()> {
+    let mut read_magic = [0; 8];
+    reader
+        .read_exact(&mut read_magic)
+        .with_context(|| format!("Failed to read the {what} header"))?;
+
+    if &read_magic != magic {
+        bail!("Not a jwtop {what}");
+    }
+
+    let mut read_version = [0; 2];
+    reader
+        .read_exact(&mut read_version)
+        .with_context(|| format!("Failed to read the {what} version"))?;
+    let read_version = u16::from_le_bytes(read_version);
+
+    if read_version != version {
+        bail!("Unsupported {what} version {read_version}, this jwtop reads version {version}");
+    }
+
+    Ok(())
+}
+
+/// Writes a single length-prefixed frame, usually a snapshot.
+pub fn write_frame(writer: &mut impl Write, frame: &impl Serialize) -> Result<()> {
+    let frame = bincode::serialize(frame).context("Failed to serialize a frame")?;
+
+    writer.write_all(&(frame.len() as u32).to_le_bytes())?;
+    writer.write_all(&frame)?;
+
+    Ok(())
+}
+
+/// The error of [`read_frame`] when the stream ends in the middle of a frame, as it does
+/// when the writer was killed while writing it.
+#[derive(Debug)]
+pub struct Truncated;
+
+impl std::fmt::Display for Truncated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("The last frame is cut off")
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+/// Reads a single frame written by [`write_frame`]. Returns `None` at the end of the
+/// stream, and [`Truncated`] when the last frame was cut off.
+pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> Result<Option<T>> {
+    let mut len = [0; 4];
+    match read_until_end(reader, &mut len)? {
+        0 => return Ok(None),
+        4 => {}
+        _ => return Err(Truncated.into()),
+    }
+
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        bail!("Frame of {len} bytes is too long");
+    }
+
+    let mut frame = vec![0; len];
+    if read_until_end(reader, &mut frame)? < len {
+        return Err(Truncated.into());
+    }
+
+    Ok(Some(
+        bincode::deserialize(&frame).context("Failed to deserialize a frame")?,
+    ))
+}
+
+/// Fills `buf` like [`Read::read_exact`], but returns how much was read when the stream
+/// ends first.
+fn read_until_end(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(read)
+}
+
+/// Appends every snapshot to a recording file.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create {}", path.to_string_lossy()))?;
+        let mut writer = BufWriter::new(file);
+        write_header(&mut writer)?;
+
+        Ok(Self { writer })
+    }
+
+    pub fn record(&mut self, snapshot: &Snapshot) -> Result<()> {
+        write_frame(&mut self.writer, snapshot)?;
+        // flush every frame, so that the recording survives jwtop being killed
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Plays back a recording made with [`Recorder`].
+pub struct Replay {
+    snapshots: Vec<Snapshot>,
+    /// Index of the newest snapshot shown, with a fractional part for slow playback.
+    position: f64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+impl Replay {
+    pub const MIN_SPEED: f64 = 0.25;
+    pub const MAX_SPEED: f64 = 64.0;
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.to_string_lossy()))?;
+        let mut reader = BufReader::new(file);
+
+        read_header(&mut reader)
+            .with_context(|| format!("Failed to load {}", path.to_string_lossy()))?;
+
+        let mut snapshots = Vec::new();
+        loop {
+            match read_frame(&mut reader) {
+                Ok(Some(snapshot)) => snapshots.push(snapshot),
+                Ok(None) => break,
+                // what was recorded before jwtop got killed is still worth replaying
+                Err(e) if e.is::<Truncated>() => {
+                    eprintln!(
+                        "The recording {} ends in a partial frame, replaying the {} complete ones",
+                        path.to_string_lossy(),
+                        snapshots.len()
+                    );
+                    break;
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to load {}", path.to_string_lossy()))
+                }
+            }
+        }
+
+        if snapshots.is_empty() {
+            bail!("The recording {} is empty", path.to_string_lossy());
+        }
+
+        Ok(Self::new(snapshots))
+    }
+
+    /// Plays back `snapshots`, which must not be empty, from the first one.
+    pub fn new(snapshots: Vec<Snapshot>) -> Self {
+        Self {
+            snapshots,
+            position: 0.0,
+            playing: true,
+            speed: 1.0,
+        }
+    }
+
+    /// Index of the newest snapshot shown.
+    pub fn position(&self) -> usize {
+        self.position as usize
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn current(&self) -> &Snapshot {
+        &self.snapshots[self.position()]
+    }
+
+    /// The snapshot the recording starts with.
+    pub fn first(&self) -> &Snapshot {
+        &self.snapshots[0]
+    }
+
+    pub fn last(&self) -> &Snapshot {
+        &self.snapshots[self.snapshots.len() - 1]
+    }
+
+    /// Advances the playback by one tick and returns the snapshots that were played.
+    pub fn advance(&mut self) -> &[Snapshot] {
+        if !self.playing {
+            return &[];
+        }
+
+        let old = self.position();
+        self.position = (self.position + self.speed).min((self.len() - 1) as f64);
+
+        if self.position() == self.len() - 1 {
+            self.playing = false;
+        }
+
+        &self.snapshots[old + 1..=self.position()]
+    }
+
+    /// Jumps `by` snapshots forward, or backwards if negative, and returns up to `amount`
+    /// snapshots leading up to and including the new position.
+    pub fn seek(&mut self, by: isize, amount: usize) -> &[Snapshot] {
+        let position = self
+            .position()
+            .saturating_add_signed(by)
+            .min(self.len() - 1);
+        self.position = position as f64;
+
+        &self.snapshots[(position + 1).saturating_sub(amount)..=position]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(Self::MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(Self::MIN_SPEED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(i: u64) -> Snapshot {
+        Snapshot {
+            timestamp: std::time::Duration::from_secs(i),
+            cpus: vec![i as f64, 100.0 - i as f64],
+            mem_used: i * 1024,
+            mem_total: 1 << 30,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let mut buf = Vec::new();
+        write_header(&mut buf).unwrap();
+        for i in 0..3 {
+            write_frame(&mut buf, &snapshot(i)).unwrap();
+        }
+        // a frame cut off in the middle, like after a crash
+        buf.extend_from_slice(&[200, 0, 0, 0, 1, 2]);
+
+        let mut reader = buf.as_slice();
+        read_header(&mut reader).unwrap();
+
+        let mut read = Vec::<Snapshot>::new();
+        let truncated = loop {
+            match read_frame(&mut reader) {
+                Ok(Some(s)) => read.push(s),
+                Ok(None) => break false,
+                Err(e) => break e.is::<Truncated>(),
+            }
+        };
+
+        assert!(truncated);
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[2].mem_used, 2048);
+        assert_eq!(read[1].cpus, vec![1.0, 99.0]);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(read_header(&mut b"not a recording".as_slice()).is_err());
+
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(read_header(&mut buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn playback() {
+        let mut replay = Replay {
+            snapshots: (0..10).map(snapshot).collect(),
+            position: 0.0,
+            playing: true,
+            speed: 1.0,
+        };
+
+        assert_eq!(replay.advance().len(), 1);
+
+        replay.slower();
+        assert_eq!(replay.advance().len(), 0);
+        assert_eq!(replay.advance().len(), 1);
+
+        replay.speed = 4.0;
+        assert_eq!(replay.advance().len(), 4);
+        assert_eq!(replay.advance().len(), 3);
+        assert!(!replay.playing);
+
+        assert_eq!(replay.seek(-3, 5).len(), 5);
+        assert_eq!(replay.position(), 6);
+        assert_eq!(replay.seek(-100, 5).len(), 1);
+    }
+}
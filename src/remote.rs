@@ -0,0 +1,421 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    app::HISTORY_LEN,
+    collector::{Collector, Snapshot},
+    exporter::Exporter,
+    recording::{read_frame, read_header, write_frame, write_header},
+};
+
+/// How long a client waits before trying to reach an agent again.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How many snapshots may wait to be sent to a client before it counts as stalled and is
+/// dropped.
+const CLIENT_QUEUE_LEN: usize = 16;
+
+/// How long writing to a client may block before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where an agent listens, written as `host:port` or `unix:/path/to/socket`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl std::str::FromStr for Address {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(Address::Unix(path.into()));
+
+            #[cfg(not(unix))]
+            return Err(anyhow!(
+                "unix sockets are not supported on this platform: {path}"
+            ));
+        }
+
+        if !s.contains(':') {
+            return Err(anyhow!(
+                "\"{s}\" is not an address, use host:port or unix:/path/to/socket"
+            ));
+        }
+
+        Ok(Address::Tcp(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Tcp(addr) => f.write_str(addr),
+            #[cfg(unix)]
+            Address::Unix(path) => write!(f, "unix:{}", path.to_string_lossy()),
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(address: &Address) -> io::Result<Self> {
+        match address {
+            Address::Tcp(addr) => TcpStream::connect(addr).map(Stream::Tcp),
+            #[cfg(unix)]
+            Address::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.set_write_timeout(timeout),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.set_write_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.flush(),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.flush(),
+        }
+    }
+}
+
+/// Clients connected to an agent, together with the snapshots sent to them so far.
+#[derive(Default)]
+struct Clients {
+    /// Snapshots waiting to be written to every client by its own thread.
+    queues: Vec<SyncSender<Arc<Snapshot>>>,
+    /// Sent to newly connected clients, so that their charts don't start out empty.
+    backlog: VecDeque<Arc<Snapshot>>,
+}
+
+impl Clients {
+    /// Starts writing to the client from a thread of its own, so that a client not reading
+    /// holds up neither sampling nor the other clients.
+    fn add(&mut self, stream: Stream) {
+        let (sender, receiver) = mpsc::sync_channel(CLIENT_QUEUE_LEN);
+        let backlog = self.backlog.clone();
+
+        thread::spawn(move || write_loop(stream, backlog, receiver));
+        self.queues.push(sender);
+    }
+
+    fn send(&mut self, snapshot: Snapshot) {
+        let snapshot = Arc::new(snapshot);
+
+        // a full queue means the client fell behind, a closed one that writing failed
+        self.queues
+            .retain(|queue| queue.try_send(snapshot.clone()).is_ok());
+
+        if self.backlog.len() == HISTORY_LEN {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(snapshot);
+    }
+}
+
+/// Writes the backlog and then every queued snapshot to a client, until writing fails or
+/// the client is dropped.
+fn write_loop(stream: Stream, backlog: VecDeque<Arc<Snapshot>>, queue: Receiver<Arc<Snapshot>>) {
+    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut stream = BufWriter::new(stream);
+
+    let sent = write_header(&mut stream).is_ok()
+        && backlog
+            .iter()
+            .all(|s| write_frame(&mut stream, &**s).is_ok())
+        && stream.flush().is_ok();
+    if !sent {
+        return;
+    }
+
+    for snapshot in queue {
+        if write_frame(&mut stream, &*snapshot).is_err() || stream.flush().is_err() {
+            return;
+        }
+    }
+}
+
+fn accept_loop<S>(clients: Arc<Mutex<Clients>>, incoming: impl Iterator<Item = io::Result<S>>)
+where
+    S: Into<Stream>,
+{
+    for stream in incoming.flatten() {
+        clients.lock().unwrap().add(stream.into());
+    }
+}
+
+impl From<TcpStream> for Stream {
+    fn from(s: TcpStream) -> Self {
+        Stream::Tcp(s)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixStream> for Stream {
+    fn from(s: UnixStream) -> Self {
+        Stream::Unix(s)
+    }
+}
+
+/// Samples this system every interval and streams the snapshots to every connected client,
+/// and to `exporter` if there is one. Never returns unless listening fails.
+pub fn run_agent(
+    mut collector: Collector,
+    address: &Address,
+    interval: Duration,
+    exporter: Option<Exporter>,
+) -> Result<()> {
+    let clients = Arc::new(Mutex::new(Clients::default()));
+
+    match address {
+        Address::Tcp(addr) => {
+            let listener =
+                TcpListener::bind(addr).with_context(|| format!("Failed to listen on {addr}"))?;
+            let clients = clients.clone();
+            thread::spawn(move || accept_loop(clients, listener.incoming()));
+        }
+
+        #[cfg(unix)]
+        Address::Unix(path) => {
+            // a socket left behind by a previous agent would make binding fail
+            if path.exists() {
+                std::fs::remove_file(path).with_context(|| {
+                    format!("Failed to remove the old socket {}", path.to_string_lossy())
+                })?;
+            }
+
+            let listener = UnixListener::bind(path)
+                .with_context(|| format!("Failed to listen on {}", path.to_string_lossy()))?;
+            let clients = clients.clone();
+            thread::spawn(move || accept_loop(clients, listener.incoming()));
+        }
+    }
+
+    loop {
+        thread::sleep(interval);
+        let snapshot = collector.sample();
+        if let Some(exporter) = &exporter {
+            exporter.update(&snapshot);
+        }
+        clients.lock().unwrap().send(snapshot);
+    }
+}
+
+/// What a [`RemoteFeed`] hears from its connection thread.
+enum Message {
+    Snapshot(Box<Snapshot>),
+    Disconnected(String),
+}
+
+/// Snapshots streamed from an agent. The connection is reestablished in the background
+/// whenever it drops.
+pub struct RemoteFeed {
+    pub address: Address,
+    receiver: Receiver<Message>,
+    /// Why the agent can't be reached at the moment, if it can't.
+    pub error: Option<String>,
+}
+
+impl RemoteFeed {
+    pub fn connect(address: Address) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        {
+            let address = address.clone();
+            thread::spawn(move || loop {
+                let error = match receive(&address, |s| {
+                    sender.send(Message::Snapshot(Box::new(s))).is_ok()
+                }) {
+                    Ok(()) => "the agent closed the connection".to_string(),
+                    Err(e) => format!("{e:#}"),
+                };
+
+                if sender.send(Message::Disconnected(error)).is_err() {
+                    return;
+                }
+
+                thread::sleep(RECONNECT_INTERVAL);
+            });
+        }
+
+        Self {
+            address,
+            receiver,
+            error: None,
+        }
+    }
+
+    /// Waits for the first snapshot, giving up after `timeout`.
+    pub fn first(&mut self, timeout: Duration) -> Result<Snapshot> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(Message::Snapshot(s)) => {
+                self.error = None;
+                Ok(*s)
+            }
+            Ok(Message::Disconnected(e)) => {
+                Err(anyhow!("Failed to connect to {}: {e}", self.address))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                Err(anyhow!("No data from {} in {timeout:?}", self.address))
+            }
+            Err(RecvTimeoutError::Disconnected) => unreachable!("the feed thread never exits"),
+        }
+    }
+
+    /// Snapshots received since the last poll.
+    pub fn poll(&mut self) -> Vec<Snapshot> {
+        let mut snapshots = Vec::new();
+
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Snapshot(s) => {
+                    self.error = None;
+                    snapshots.push(*s);
+                }
+                Message::Disconnected(e) => self.error = Some(e),
+            }
+        }
+
+        snapshots
+    }
+}
+
+/// Connects to an agent and hands every received snapshot to `on_snapshot` until either
+/// the connection drops or `on_snapshot` returns false.
+fn receive(address: &Address, mut on_snapshot: impl FnMut(Snapshot) -> bool) -> Result<()> {
+    let mut stream = BufReader::new(Stream::connect(address)?);
+    read_header(&mut stream)?;
+
+    while let Some(snapshot) = read_frame(&mut stream)? {
+        if !on_snapshot(snapshot) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses() {
+        assert_eq!(
+            "localhost:4242".parse::<Address>().unwrap(),
+            Address::Tcp("localhost:4242".into())
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            "unix:/tmp/jwtop.sock".parse::<Address>().unwrap(),
+            Address::Unix("/tmp/jwtop.sock".into())
+        );
+        assert!("localhost".parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn streams_to_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = Address::Tcp(listener.local_addr().unwrap().to_string());
+
+        let clients = Arc::new(Mutex::new(Clients::default()));
+        clients.lock().unwrap().send(Snapshot {
+            mem_used: 1,
+            ..Default::default()
+        });
+
+        {
+            let clients = clients.clone();
+            thread::spawn(move || accept_loop(clients, listener.incoming()));
+        }
+
+        let mut feed = RemoteFeed::connect(address);
+        assert_eq!(feed.first(Duration::from_secs(5)).unwrap().mem_used, 1);
+
+        clients.lock().unwrap().send(Snapshot {
+            mem_used: 2,
+            ..Default::default()
+        });
+
+        let mut received = Vec::new();
+        for _ in 0..50 {
+            received.extend(feed.poll());
+            if !received.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].mem_used, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn drops_stalled_clients() {
+        // the other end never reads, so the socket buffer fills up
+        let (stream, _peer) = UnixStream::pair().unwrap();
+
+        let mut clients = Clients::default();
+        clients.add(Stream::Unix(stream));
+
+        for _ in 0..CLIENT_QUEUE_LEN * 4 {
+            clients.send(Snapshot {
+                cpus: vec![0.0; 100_000],
+                ..Default::default()
+            });
+        }
+
+        assert!(clients.queues.is_empty());
+        assert_eq!(clients.backlog.len(), HISTORY_LEN);
+    }
+}
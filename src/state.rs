@@ -0,0 +1,120 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Settings changed from the interface, kept between runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Names of the hidden chart series, by the name of their pane.
+    pub hidden_series: BTreeMap<String, BTreeSet<String>>,
+    /// Where the state is saved, `None` if it isn't.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl State {
+    /// The file in the data directory the state is kept in.
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("org", "jw", "jwtop")
+            .map(|dirs| dirs.data_dir().join("state.toml"))
+    }
+
+    /// Loads the state saved at `path`, or starts with an empty one if there is none. A file
+    /// that can't be parsed, e.g. one of an older jwtop, is moved aside.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut state = match fs::read_to_string(path) {
+            Ok(state) => match toml::from_str::<State>(&state) {
+                Ok(state) => state,
+                Err(e) => {
+                    let mut old = path.as_os_str().to_owned();
+                    old.push(".old");
+                    fs::rename(path, &old).with_context(|| {
+                        format!("Failed to move {} aside", path.to_string_lossy())
+                    })?;
+
+                    eprintln!(
+                        "Ignoring the state in {}, moved it to {}: {e}",
+                        path.to_string_lossy(),
+                        old.to_string_lossy()
+                    );
+                    State::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read state from {}", path.to_string_lossy())
+                })
+            }
+        };
+
+        state.path = Some(path.to_path_buf());
+        Ok(state)
+    }
+
+    /// Writes the state back to where it was loaded from.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to save state to {}", path.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("jwtop-state-{}", std::process::id()))
+            .join("state.toml");
+
+        let mut state = State::load(&path).unwrap();
+        assert!(state.hidden_series.is_empty());
+
+        state
+            .hidden_series
+            .entry("disks".into())
+            .or_default()
+            .insert("sda w".into());
+        state.save().unwrap();
+
+        let loaded = State::load(&path).unwrap();
+        assert_eq!(loaded.hidden_series, state.hidden_series);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn moves_unreadable_state_aside() {
+        let dir = std::env::temp_dir().join(format!("jwtop-bad-state-{}", std::process::id()));
+        let path = dir.join("state.toml");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "hidden_series = 42\n").unwrap();
+
+        let state = State::load(&path).unwrap();
+        assert!(state.hidden_series.is_empty());
+        assert!(!path.exists());
+        assert!(dir.join("state.toml.old").exists());
+
+        // the fresh state is saved where the unreadable one was
+        state.save().unwrap();
+        assert!(State::load(&path).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,398 @@
+mod store;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+pub use store::Store;
+
+use crate::{
+    app::History,
+    collector::{CpuTimes, DiskInfo, PowerInfo, Pressure, PressureStats, Snapshot},
+};
+
+/// A resolution history is kept at, and for how long.
+#[derive(Debug, Clone, Copy)]
+pub struct Tier {
+    /// The time every point covers.
+    pub resolution: Duration,
+    /// How many points are kept.
+    pub capacity: usize,
+}
+
+impl Tier {
+    /// The time all points of the tier together cover.
+    pub fn span(&self) -> Duration {
+        self.resolution * self.capacity as u32
+    }
+}
+
+/// 1 second for 10 minutes, 10 seconds for 6 hours and 1 minute for a week.
+pub const TIERS: [Tier; 3] = [
+    Tier {
+        resolution: Duration::from_secs(1),
+        capacity: 600,
+    },
+    Tier {
+        resolution: Duration::from_secs(10),
+        capacity: 2160,
+    },
+    Tier {
+        resolution: Duration::from_secs(60),
+        capacity: 10080,
+    },
+];
+
+/// The minimum, average and maximum of the samples of a metric that fell into a point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub min: f32,
+    pub avg: f32,
+    pub max: f32,
+}
+
+impl Aggregate {
+    /// Adds the `count`th sample.
+    fn add(&mut self, value: f64, count: u32) {
+        let value = value as f32;
+
+        if count <= 1 {
+            *self = Aggregate {
+                min: value,
+                avg: value,
+                max: value,
+            };
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            self.avg += (value - self.avg) / count as f32;
+        }
+    }
+}
+
+fn add_all(
+    aggregates: &mut Vec<Aggregate>,
+    values: impl ExactSizeIterator<Item = f64>,
+    count: u32,
+) {
+    aggregates.resize_with(values.len(), Default::default);
+    for (aggregate, value) in aggregates.iter_mut().zip(values) {
+        aggregate.add(value, count);
+    }
+}
+
+/// Every sample taken during the time covered by a point of a tier, aggregated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Point {
+    /// Timestamp of the first sample.
+    pub timestamp: Duration,
+    /// How many samples were aggregated.
+    pub count: u32,
+    pub cpus: Vec<Aggregate>,
+    pub frequencies: Vec<Aggregate>,
+    /// How all cpus together spent their time, in the order of [`CpuTimes::NAMES`].
+    pub cpu_times: Vec<Aggregate>,
+    pub mem: Aggregate,
+    pub mem_total: u64,
+    /// What every disk did per sample, in the order of [`DiskInfo::values`].
+    pub disks: BTreeMap<String, Vec<Aggregate>>,
+    /// Charge of the batteries, `None` without batteries.
+    pub battery: Option<Aggregate>,
+    /// The 10 second averages of the stall information in the order of [`Pressure::NAMES`],
+    /// empty without it.
+    pub pressure: Vec<Aggregate>,
+}
+
+impl Point {
+    fn new(snapshot: &Snapshot) -> Self {
+        let mut point = Point {
+            timestamp: snapshot.timestamp,
+            ..Default::default()
+        };
+        point.add(snapshot);
+        point
+    }
+
+    fn add(&mut self, snapshot: &Snapshot) {
+        self.count += 1;
+        let count = self.count;
+
+        add_all(&mut self.cpus, snapshot.cpus.iter().copied(), count);
+        add_all(
+            &mut self.frequencies,
+            snapshot.frequencies.iter().map(|&f| f as f64),
+            count,
+        );
+        add_all(
+            &mut self.cpu_times,
+            snapshot.total_cpu_times.values().into_iter(),
+            count,
+        );
+        self.mem.add(snapshot.mem_used as f64, count);
+        self.mem_total = snapshot.mem_total;
+
+        for (name, info) in &snapshot.disks {
+            add_all(
+                self.disks.entry(name.clone()).or_default(),
+                info.values().into_iter(),
+                count,
+            );
+        }
+
+        if let Some(power) = &snapshot.power {
+            self.battery
+                .get_or_insert_with(Default::default)
+                .add(power.charge, count);
+        }
+
+        if let Some(pressure) = &snapshot.pressure {
+            add_all(
+                &mut self.pressure,
+                pressure.stats().into_iter().map(|stats| stats.avg10),
+                count,
+            );
+        }
+    }
+
+    /// A snapshot of the averages, to be shown like any other.
+    pub fn average(&self) -> Snapshot {
+        self.snapshot(|a| a.avg)
+    }
+
+    /// A snapshot of the lowest samples.
+    pub fn minimum(&self) -> Snapshot {
+        self.snapshot(|a| a.min)
+    }
+
+    /// A snapshot of the highest samples.
+    pub fn maximum(&self) -> Snapshot {
+        self.snapshot(|a| a.max)
+    }
+
+    /// A snapshot of what `pick` takes from every aggregate.
+    fn snapshot(&self, pick: fn(&Aggregate) -> f32) -> Snapshot {
+        let values = |aggregates: &[Aggregate]| -> Vec<f64> {
+            aggregates.iter().map(|a| pick(a) as f64).collect()
+        };
+        let [user, system, iowait, irq, steal] = std::array::from_fn(|i| {
+            self.cpu_times
+                .get(i)
+                .map_or(0.0, |aggregate| pick(aggregate) as f64)
+        });
+
+        Snapshot {
+            timestamp: self.timestamp,
+            cpus: values(&self.cpus),
+            frequencies: values(&self.frequencies)
+                .into_iter()
+                .map(|f| f as u64)
+                .collect(),
+            total_cpu_times: CpuTimes {
+                user,
+                system,
+                iowait,
+                irq,
+                steal,
+            },
+            mem_used: pick(&self.mem) as u64,
+            mem_total: self.mem_total,
+            disks: self
+                .disks
+                .iter()
+                .map(|(name, aggregates)| {
+                    let mut values: [f64; DiskInfo::FIELDS] = std::array::from_fn(|i| {
+                        aggregates
+                            .get(i)
+                            .map_or(0.0, |aggregate| pick(aggregate) as f64)
+                    });
+                    // rates are always over the average time between samples, dividing the
+                    // fewest sectors by the shortest time would be neither
+                    values[DiskInfo::FIELDS - 1] = aggregates
+                        .get(DiskInfo::FIELDS - 1)
+                        .map_or(0.0, |aggregate| aggregate.avg as f64);
+                    (name.clone(), DiskInfo::from_values(values))
+                })
+                .collect(),
+            pressure: (!self.pressure.is_empty()).then(|| {
+                Pressure::from_stats(std::array::from_fn(|i| PressureStats {
+                    avg10: self.pressure.get(i).map_or(0.0, |a| pick(a) as f64),
+                    ..Default::default()
+                }))
+            }),
+            power: self.battery.map(|battery| PowerInfo {
+                charge: pick(&battery) as f64,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// The points of a single tier, the newest one still collecting samples.
+#[derive(Debug, Clone)]
+struct TierPoints {
+    tier: Tier,
+    points: VecDeque<Point>,
+}
+
+impl TierPoints {
+    fn slot(&self, timestamp: Duration) -> u64 {
+        timestamp.as_secs() / self.tier.resolution.as_secs()
+    }
+
+    /// Adds the snapshot to the newest point, or starts a new one once the snapshot falls
+    /// outside of it. Returns the point completed by starting a new one.
+    fn push(&mut self, snapshot: &Snapshot) -> Option<Point> {
+        let slot = self.slot(snapshot.timestamp);
+        let newest = self.points.back().map(|point| self.slot(point.timestamp));
+
+        match self.points.back_mut() {
+            Some(point) if newest == Some(slot) => {
+                point.add(snapshot);
+                None
+            }
+            _ => {
+                let completed = self.points.back().cloned();
+                self.push_point(Point::new(snapshot));
+                completed
+            }
+        }
+    }
+
+    fn push_point(&mut self, point: Point) {
+        if self.points.len() == self.tier.capacity {
+            self.points.pop_front();
+        }
+        self.points.push_back(point);
+    }
+}
+
+/// Snapshots aggregated at the resolutions of [`TIERS`], each tier keeping a fixed amount of
+/// points so that memory use stays bounded.
+#[derive(Debug, Clone)]
+pub struct Tiers {
+    tiers: Vec<TierPoints>,
+}
+
+impl Default for Tiers {
+    fn default() -> Self {
+        Self {
+            tiers: TIERS
+                .iter()
+                .map(|&tier| TierPoints {
+                    tier,
+                    points: VecDeque::with_capacity(tier.capacity),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Tiers {
+    /// Adds the snapshot to every tier. Returns the points it completed, with the index of
+    /// their tier.
+    pub fn push(&mut self, snapshot: &Snapshot) -> Vec<(usize, Point)> {
+        self.tiers
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, tier)| Some((i, tier.push(snapshot)?)))
+            .collect()
+    }
+
+    /// Adds a point aggregated earlier to the tier, replacing the newest point if both cover
+    /// the same time. Points older than the newest one are ignored.
+    pub fn insert(&mut self, tier: usize, point: Point) {
+        let tier = &mut self.tiers[tier];
+        let slot = tier.slot(point.timestamp);
+
+        match tier.points.back().map(|newest| tier.slot(newest.timestamp)) {
+            Some(newest) if newest > slot => {}
+            Some(newest) if newest == slot => *tier.points.back_mut().unwrap() = point,
+            _ => tier.push_point(point),
+        }
+    }
+
+    /// The aggregated points of the tier, oldest first.
+    pub fn points(&self, tier: usize) -> impl Iterator<Item = &Point> {
+        self.tiers[tier].points.iter()
+    }
+
+    /// The newest point of the tier, still collecting samples.
+    pub fn newest(&self, tier: usize) -> Option<&Point> {
+        self.tiers[tier].points.back()
+    }
+
+    /// The averages of the tier as a history as long as the tier, with the lowest and
+    /// highest samples as its bounds.
+    pub fn history(&self, tier: usize) -> History {
+        let tier = &self.tiers[tier];
+        let cpus = tier.points.back().map_or(0, |p| p.cpus.len());
+
+        let mut history = History::with_len(cpus, tier.tier.capacity);
+        let mut bounds = [history.clone(), history.clone()];
+        for point in &tier.points {
+            history.push(point.average());
+            bounds[0].push(point.minimum());
+            bounds[1].push(point.maximum());
+        }
+        history.bounds = Some(Box::new(bounds));
+        history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(secs: u64, cpu: f64) -> Snapshot {
+        Snapshot {
+            timestamp: Duration::from_secs(secs),
+            cpus: vec![cpu],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn aggregation() {
+        let mut tiers = Tiers::default();
+        for (secs, cpu) in [(100, 10.0), (105, 30.0), (109, 20.0), (110, 50.0)] {
+            tiers.push(&snapshot(secs, cpu));
+        }
+
+        assert_eq!(tiers.points(0).count(), 4);
+
+        let points = tiers.points(1).collect::<Vec<_>>();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].count, 3);
+        assert_eq!(
+            points[0].cpus[0],
+            Aggregate {
+                min: 10.0,
+                avg: 20.0,
+                max: 30.0
+            }
+        );
+
+        let history = tiers.history(1);
+        assert_eq!(history.capacity(), TIERS[1].capacity);
+        assert_eq!(history.collected(), 2);
+        assert_eq!(history.cpus[0][history.index(1)], 20.0);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut tiers = Tiers::default();
+        for secs in 1..=TIERS[0].capacity as u64 + 10 {
+            tiers.push(&snapshot(secs, 0.0));
+        }
+
+        assert_eq!(tiers.points(0).count(), TIERS[0].capacity);
+        assert_eq!(
+            tiers.points(0).next().unwrap().timestamp,
+            Duration::from_secs(11)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+
+use super::{Point, Tiers, TIERS};
+use crate::{
+    config::HistoryConfig,
+    recording::{read_frame, read_versioned_header, write_frame, write_versioned_header},
+};
+
+const MAGIC: &[u8; 8] = b"JWTOPHIS";
+
+/// Bumped whenever the layout of [`Point`] or [`TIERS`] changes.
+pub const VERSION: u16 = 1;
+
+/// A file the completed points of every tier are appended to, so that the long-term
+/// history survives restarts.
+pub struct Store {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+    retention: Duration,
+    max_size: u64,
+}
+
+impl Store {
+    /// The file in the data directory the history is kept in by default.
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("org", "jw", "jwtop")
+            .map(|dirs| dirs.data_dir().join("history.bin"))
+    }
+
+    /// Opens the store, loading the points it keeps into `tiers`.
+    ///
+    /// A file of another version or format is moved aside to `<path>.old` instead of being
+    /// overwritten, and points after a corrupted one are dropped.
+    pub fn open(config: &HistoryConfig, tiers: &mut Tiers) -> Result<Self> {
+        let path = match &config.path {
+            Some(path) => path.clone(),
+            None => Self::default_path().context("Failed to find the data directory")?,
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.to_string_lossy()))?;
+        }
+
+        let retention = Duration::from_secs(config.retention_days * 24 * 60 * 60);
+        load(&path, retention, tiers)?;
+
+        let mut store = Self {
+            writer: BufWriter::new(open_append(&path)?),
+            path,
+            size: 0,
+            retention,
+            max_size: config.max_size,
+        };
+        // starts from a file holding exactly what was loaded, without expired points or
+        // whatever followed a corrupted one
+        store.compact(tiers)?;
+
+        Ok(store)
+    }
+
+    /// Appends the points completed by [`Tiers::push`], compacting the file once it grows
+    /// past the size limit.
+    pub fn append(&mut self, completed: &[(usize, Point)], tiers: &Tiers) -> Result<()> {
+        for (tier, point) in completed {
+            let frame = (*tier as u8, point);
+            self.size += bincode::serialized_size(&frame)? + 4;
+            write_frame(&mut self.writer, &frame)?;
+        }
+        // flush every append, so that the history survives jwtop being killed
+        self.writer.flush()?;
+
+        if self.size > self.max_size {
+            self.compact(tiers)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the file with only the points the tiers still keep, dropping expired ones.
+    /// If those still take up more than half of the size limit the oldest points of the
+    /// finest tiers are dropped, as the coarser tiers cover the same time.
+    fn compact(&mut self, tiers: &Tiers) -> Result<()> {
+        let oldest = now().saturating_sub(self.retention);
+
+        let mut frames = (0..TIERS.len())
+            .map(|tier| {
+                tiers
+                    .points(tier)
+                    .filter(|point| point.timestamp >= oldest)
+                    .map(|point| {
+                        let mut frame = Vec::new();
+                        write_frame(&mut frame, &(tier as u8, point))?;
+                        Ok(frame)
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut size = frames.iter().flatten().map(|f| f.len() as u64).sum::<u64>();
+        for tier in &mut frames {
+            let dropped = tier
+                .iter()
+                .take_while(|frame| {
+                    let over = size > self.max_size / 2;
+                    if over {
+                        size -= frame.len() as u64;
+                    }
+                    over
+                })
+                .count();
+            tier.drain(..dropped);
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(
+            File::create(&tmp_path)
+                .with_context(|| format!("Failed to create {}", tmp_path.to_string_lossy()))?,
+        );
+        write_versioned_header(&mut writer, MAGIC, VERSION)?;
+        for frame in frames.iter().flatten() {
+            writer.write_all(frame)?;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path.to_string_lossy()))?;
+
+        self.writer = BufWriter::new(open_append(&self.path)?);
+        self.size = self.writer.get_ref().metadata()?.len();
+
+        Ok(())
+    }
+}
+
+/// Loads the points of the file at `path` that are younger than `retention` into `tiers`.
+fn load(path: &Path, retention: Duration, tiers: &mut Tiers) -> Result<()> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open {}", path.to_string_lossy()))
+        }
+    };
+
+    let empty = file.metadata()?.len() == 0;
+    let mut reader = BufReader::new(file);
+
+    if read_versioned_header(&mut reader, MAGIC, VERSION, "history").is_err() {
+        if !empty {
+            let mut old = path.as_os_str().to_owned();
+            old.push(".old");
+            fs::rename(path, &old)
+                .with_context(|| format!("Failed to move {} aside", path.to_string_lossy()))?;
+        }
+
+        return Ok(());
+    }
+
+    let oldest = now().saturating_sub(retention);
+
+    // a frame that can't be read means the rest of the file can't be trusted either
+    while let Ok(Some((tier, point))) = read_frame::<(u8, Point)>(&mut reader) {
+        if (tier as usize) < TIERS.len() && point.timestamp >= oldest {
+            tiers.insert(tier as usize, point);
+        }
+    }
+
+    Ok(())
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.to_string_lossy()))
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Snapshot;
+
+    fn config(path: &Path) -> HistoryConfig {
+        HistoryConfig {
+            path: Some(path.to_path_buf()),
+            retention_days: 1,
+            max_size: 1024 * 1024,
+        }
+    }
+
+    fn snapshot(timestamp: Duration) -> Snapshot {
+        Snapshot {
+            timestamp,
+            cpus: vec![50.0],
+            ..Default::default()
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("jwtop-history-{}-{name}", std::process::id()))
+            .join("history.bin")
+    }
+
+    #[test]
+    fn survives_restarts() {
+        let path = temp_path("restarts");
+        let start = now() - Duration::from_secs(60);
+
+        let mut tiers = Tiers::default();
+        let mut store = Store::open(&config(&path), &mut tiers).unwrap();
+        for secs in 0..30 {
+            let completed = tiers.push(&snapshot(start + Duration::from_secs(secs)));
+            store.append(&completed, &tiers).unwrap();
+        }
+        drop(store);
+
+        let mut loaded = Tiers::default();
+        Store::open(&config(&path), &mut loaded).unwrap();
+        // the newest point of every tier was still collecting samples
+        assert_eq!(loaded.points(0).count(), 29);
+        assert_eq!(
+            loaded.points(0).last().unwrap().timestamp,
+            start + Duration::from_secs(28)
+        );
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn drops_corrupted_and_expired_points() {
+        let path = temp_path("corrupted");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let expired = now() - Duration::from_secs(2 * 24 * 60 * 60);
+        let recent = now() - Duration::from_secs(10);
+
+        let mut buf = Vec::new();
+        write_versioned_header(&mut buf, MAGIC, VERSION).unwrap();
+        for timestamp in [expired, recent] {
+            write_frame(&mut buf, &(0u8, Point::new(&snapshot(timestamp)))).unwrap();
+        }
+        buf.extend_from_slice(&[3, 0, 0, 0, 0xff, 0xff, 0xff]);
+        write_frame(
+            &mut buf,
+            &(0u8, Point::new(&snapshot(recent + Duration::from_secs(1)))),
+        )
+        .unwrap();
+        fs::write(&path, buf).unwrap();
+
+        let mut tiers = Tiers::default();
+        Store::open(&config(&path), &mut tiers).unwrap();
+        assert_eq!(tiers.points(0).count(), 1);
+        assert_eq!(tiers.points(0).next().unwrap().timestamp, recent);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn moves_other_versions_aside() {
+        let path = temp_path("version");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let mut buf = Vec::new();
+        write_versioned_header(&mut buf, MAGIC, VERSION + 1).unwrap();
+        fs::write(&path, &buf).unwrap();
+
+        let mut tiers = Tiers::default();
+        Store::open(&config(&path), &mut tiers).unwrap();
+        assert_eq!(tiers.points(0).count(), 0);
+        assert_eq!(fs::read(path.with_extension("bin.old")).unwrap(), buf);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
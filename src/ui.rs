@@ -1,18 +1,45 @@
-use std::rc::Rc;
+use std::{collections::VecDeque, rc::Rc};
 
 use tui::{
     prelude::*,
-    widgets::{Block, BorderType, Borders},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Paragraph,
+    },
 };
 
-use crate::app::App;
+use crate::{
+    app::{App, CpuTimes, CpuView, DiskView, MemPrefix, Pane, Source},
+    config::Action,
+};
 
-use self::{chart_wrapper::ChartWrapper, cpus_bars::CpusBars, disks::Disks, processes::Processes};
+use self::{
+    battery::Battery,
+    cgroups::Cgroups,
+    chart_wrapper::{ChartWrapper, Unit},
+    cpus_bars::{format_frequency, CpusBars},
+    disks::Disks,
+    filesystems::Filesystems,
+    heatmap::Heatmap,
+    help::Help,
+    overview::Overview,
+    pressure::Pressure,
+    processes::Processes,
+    series_list::SeriesList,
+};
 
-mod chart_wrapper;
+mod battery;
+mod cgroups;
+pub mod chart_wrapper;
 mod cpus_bars;
 mod disks;
+mod filesystems;
+mod heatmap;
+mod help;
+mod overview;
+mod pressure;
 pub mod processes;
+mod series_list;
 
 /// Renders the user interface widgets.
 pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
@@ -26,6 +53,74 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
         .borders(Borders::all())
         .border_type(BorderType::Rounded);
 
+    match &app.source {
+        Source::Hosts(hosts) if hosts.opened.is_none() => frame.render_widget(
+            Overview::new(hosts, &app.config)
+                .block(block.clone().title(Line::styled("hosts", title_style)))
+                .style(block_style),
+            frame.size(),
+        ),
+        _ => render_layout(app, frame, &block, block_style, title_style),
+    }
+
+    if app.series_list.is_some() {
+        frame.render_widget(
+            SeriesList::new(app)
+                .block(block.clone().title(Line::styled(
+                    format!("{} series", app.focus.name()),
+                    title_style,
+                )))
+                .style(block_style),
+            frame.size(),
+        );
+    }
+
+    if let Some(offset) = app.help {
+        app.help = Some(offset.min(Help::max_offset(app, frame.size())));
+
+        frame.render_widget(
+            Help::new(app)
+                .block(block.title(Line::styled("help", title_style)))
+                .style(block_style),
+            frame.size(),
+        );
+    }
+}
+
+/// Renders the usual panes showing a single system.
+fn render_layout<B: Backend>(
+    app: &App,
+    frame: &mut Frame<'_, B>,
+    block: &Block,
+    block_style: Style,
+    title_style: Style,
+) {
+    let mut area = frame.size();
+
+    let alerts = app
+        .alerts
+        .active()
+        .map(|a| a.describe())
+        .collect::<Vec<_>>();
+    if !alerts.is_empty() {
+        area.height = area.height.saturating_sub(1);
+
+        let status = Rect {
+            y: area.bottom(),
+            height: 1,
+            ..area
+        };
+        let style = Style::default()
+            .fg(*app.config.theme.widget.alert_color)
+            .add_modifier(Modifier::BOLD);
+
+        frame.render_widget(
+            Paragraph::new(Line::styled(format!(" ⚠ {}", alerts.join(" | ")), style))
+                .style(block_style),
+            status,
+        );
+    }
+
     let layout = Layout::default()
         .margin(0)
         .direction(Direction::Vertical)
@@ -34,68 +129,497 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
             Constraint::Ratio(1, 3),
             Constraint::Ratio(1, 3),
         ])
-        .split(frame.size());
+        .split(area);
+
+    let history = app.displayed_history();
+    let cursor_label = cursor_label(app);
 
-    let cpus = split_cpus(layout[0], app.cpu_history.len());
+    let battery = Battery::new(app);
+    let cpus_area = if battery.is_some() {
+        Layout::default()
+            .margin(0)
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(BATTERY_WIDTH)])
+            .split(layout[0])
+    } else {
+        Rc::from([layout[0]])
+    };
+    let cpus = split_cpus(cpus_area[0], history.cpus.len());
 
+    if let Some(battery) = battery {
+        frame.render_widget(
+            battery.style(block_style).block(pane_block(
+                app,
+                Pane::Battery,
+                block,
+                "battery",
+                title_style,
+            )),
+            cpus_area[1],
+        );
+    }
+
+    let charts_and_pressure = Layout::default()
+        .margin(0)
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(PRESSURE_WIDTH)])
+        .split(layout[1]);
     let mem_and_disks = Layout::default()
         .margin(0)
         .direction(Direction::Horizontal)
         .constraints([Constraint::Ratio(1, 2); 2])
-        .split(layout[1]);
+        .split(charts_and_pressure[0]);
 
-    frame.render_widget(
-        ChartWrapper::new(
-            &app.cpu_history,
-            Box::new(|percentage, i| format!("cpu{i}: {percentage:.1}%")),
-            [0.0, 100.0],
-            &app.config,
-        )
-        .style(block_style)
-        .block(block.clone().title(Line::styled("cpu", title_style)))
-        .label_suffix('%'),
-        cpus[0],
-    );
+    let cursor = app.cursor().unwrap_or_default();
+    let hidden = |name: &str| app.is_hidden(Pane::Cpu, name);
+    let (usage, usage_names) = plotted_cpus(&history.cpus, cursor, &hidden);
+    let (frequencies, frequency_names) = plotted_cpus(&history.frequencies, cursor, &hidden);
+
+    let cpu_chart = match app.cpu_view {
+        CpuView::Usage => Some((
+            ChartWrapper::new(
+                &usage,
+                Box::new(|percentage, i| format!("{}: {percentage:.1}%", usage_names[i])),
+                &app.config,
+            )
+            .range([0.0, 100.0])
+            .envelope(history, |h| named_cpus(&h.cpus, &usage_names))
+            .visible(visible(app, Pane::Cpu, &usage_names)),
+            "cpu",
+        )),
+
+        CpuView::Frequency => Some((
+            ChartWrapper::new(
+                &frequencies,
+                Box::new(|mhz, i| format!("{}: {}", frequency_names[i], format_frequency(mhz))),
+                &app.config,
+            )
+            .unit(Unit::MEGAHERTZ)
+            .envelope(history, |h| named_cpus(&h.frequencies, &frequency_names))
+            .visible(visible(app, Pane::Cpu, &frequency_names)),
+            "cpu frequency",
+        )),
+
+        CpuView::Breakdown => {
+            // every category is drawn on top of the previous ones
+            let stacked = (0..CpuTimes::NAMES.len())
+                .map(|category| {
+                    history
+                        .total_cpu_times
+                        .iter()
+                        .map(|times| times.values()[..=category].iter().sum())
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+
+            let shown =
+                history.total_cpu_times[history.index(app.cursor().unwrap_or_default())].values();
+
+            Some((
+                ChartWrapper::new(
+                    &stacked,
+                    Box::new(move |_, i| format!("{}: {:.1}%", CpuTimes::NAMES[i], shown[i])),
+                    &app.config,
+                )
+                .range([0.0, 100.0])
+                .stacked()
+                .visible(visible(app, Pane::Cpu, &CpuTimes::NAMES)),
+                "cpu time",
+            ))
+        }
+
+        CpuView::Heatmap => None,
+    };
+
+    match cpu_chart {
+        Some((chart, title)) => frame.render_widget(
+            chart
+                .options(app.config.charts.cpu)
+                .axis_top(app.axis_tops.get(Pane::Cpu))
+                .history(history)
+                .cursor(app.cursor(), &cursor_label)
+                .style(block_style)
+                .block(pane_block(app, Pane::Cpu, block, title, title_style)),
+            cpus[0],
+        ),
+
+        None => frame.render_widget(
+            Heatmap::new(app).style(block_style).block(pane_block(
+                app,
+                Pane::Cpu,
+                block,
+                "cpu heatmap",
+                title_style,
+            )),
+            cpus[0],
+        ),
+    }
+
+    let mut bars_block = pane_block(app, Pane::Cpu, block, "cpu", title_style);
+    if let Some(label) = source_label(app) {
+        bars_block = bars_block.title(
+            Title::from(Line::styled(format!(" {label} "), title_style))
+                .alignment(Alignment::Right),
+        );
+    }
+
+    let mut governors = history
+        .governors
+        .iter()
+        .filter(|g| !g.is_empty())
+        .collect::<Vec<_>>();
+    governors.sort();
+    governors.dedup();
+    if !governors.is_empty() {
+        let governors = governors
+            .into_iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("/");
+
+        bars_block = bars_block.title(
+            Title::from(Line::styled(
+                format!(" governor: {governors} "),
+                title_style,
+            ))
+            .position(Position::Bottom),
+        );
+    }
 
     frame.render_widget(
-        CpusBars::new(app)
-            .style(block_style)
-            .block(block.clone().title(Line::styled("cpu", title_style))),
+        CpusBars::new(app).style(block_style).block(bars_block),
         cpus[1],
     );
 
     frame.render_widget(
         ChartWrapper::new(
-            &[app.mem_history.clone()],
-            Box::new(|used_mem, _| format!("used mem: {used_mem:.1}{}", app.mem_prefix.prefix())),
-            [0.0, app.mem_total],
+            std::slice::from_ref(&history.mem),
+            Box::new(|used_mem, _| format!("used mem: {}", MemPrefix::best_string(used_mem))),
             &app.config,
         )
+        .range([0.0, history.mem_total as f64])
+        .unit(Unit::BYTES)
+        .envelope(history, |h| vec![h.mem.clone()])
+        .visible(visible(app, Pane::Memory, &["used mem"]))
+        .options(app.config.charts.mem)
+        .axis_top(app.axis_tops.get(Pane::Memory))
+        .history(history)
+        .cursor(app.cursor(), &cursor_label)
         .style(block_style)
-        .block(block.clone().title(Line::styled("mem", title_style)))
-        .label_suffix(app.mem_prefix.prefix()),
+        .block(pane_block(app, Pane::Memory, block, "mem", title_style)),
         mem_and_disks[0],
     );
 
+    let disks_title = match app.disk_view {
+        DiskView::Throughput => "disks",
+        DiskView::Iops => "disk iops",
+        DiskView::Latency => "disk latency",
+        DiskView::Utilization => "disk utilization",
+    };
+
     frame.render_widget(
         Disks::new(app)
-            .block(block.clone().title(Line::styled("disks", title_style)))
+            .block(pane_block(
+                app,
+                Pane::Disks,
+                block,
+                disks_title,
+                title_style,
+            ))
             .style(block_style),
         mem_and_disks[1],
     );
 
+    frame.render_widget(
+        Pressure::new(app)
+            .block(pane_block(
+                app,
+                Pane::Pressure,
+                block,
+                "pressure",
+                title_style,
+            ))
+            .style(block_style),
+        charts_and_pressure[1],
+    );
+
+    let mut processes_block = pane_block(app, Pane::Processes, block, "procs", title_style);
+    if app.paused.is_some() {
+        processes_block = processes_block.title(
+            Title::from(Line::styled(format!(" {cursor_label} "), title_style))
+                .alignment(Alignment::Right),
+        );
+    }
+
+    let show_cgroups = app.is_shown(Pane::Cgroups);
+    let tables = Layout::default()
+        .margin(0)
+        .direction(Direction::Horizontal)
+        .constraints(if show_cgroups {
+            [Constraint::Ratio(1, 3); 3].to_vec()
+        } else {
+            [Constraint::Ratio(1, 2); 2].to_vec()
+        })
+        .split(layout[2]);
+
     frame.render_widget(
         Processes::new(app)
-            .block(block.title(Line::styled("procs", title_style)))
+            .block(processes_block)
+            .style(block_style),
+        tables[0],
+    );
+
+    if show_cgroups {
+        frame.render_widget(
+            Cgroups::new(app)
+                .block(pane_block(
+                    app,
+                    Pane::Cgroups,
+                    block,
+                    "cgroups",
+                    title_style,
+                ))
+                .style(block_style),
+            tables[1],
+        );
+    }
+
+    frame.render_widget(
+        Filesystems::new(app)
+            .block(pane_block(app, Pane::Filesystems, block, "fs", title_style))
             .style(block_style),
-        layout[2],
-    )
+        tables[tables.len() - 1],
+    );
+}
+
+/// The block around a pane, with a double border while focused, flashing in the alert color
+/// while an alert about the pane is active.
+fn pane_block<'a>(
+    app: &App,
+    pane: Pane,
+    block: &Block<'a>,
+    title: &'a str,
+    title_style: Style,
+) -> Block<'a> {
+    let mut block = block.clone().title(Line::styled(title, title_style));
+
+    if app.focus == pane {
+        block = block.border_type(BorderType::Double);
+    }
+
+    if app.flash && app.alerts.alerted(pane) {
+        block.border_style(Style::default().fg(*app.config.theme.widget.alert_color))
+    } else {
+        block
+    }
+}
+
+/// Describes the sample shown while paused.
+pub(crate) fn cursor_label(app: &App) -> String {
+    let Some(cursor) = app.cursor() else {
+        return String::new();
+    };
+
+    let age = app.displayed_history().age(cursor).as_secs_f64();
+    let hints = format!(
+        "{}/{}",
+        app.config.keys.hint(Action::ScrubBack),
+        app.config.keys.hint(Action::ScrubForward)
+    );
+
+    if cursor == 0 {
+        format!("paused, {hints} to scrub")
+    } else {
+        format!("paused: {age:.0}s ago")
+    }
+}
+
+/// Describes where the shown data comes from, if it's not this system.
+fn source_label(app: &App) -> Option<String> {
+    let replay = match &app.source {
+        Source::Live(_) => return None,
+        Source::Remote(feed) => {
+            return Some(match &feed.error {
+                None => feed.address.to_string(),
+                Some(error) => format!("{}: {error}", feed.address),
+            })
+        }
+        Source::Hosts(hosts) => {
+            let host = hosts.opened()?;
+            return Some(match &host.feed.error {
+                None => format!("{} ({})", host.name, host.feed.address),
+                Some(error) => format!("{}: {error}", host.name),
+            });
+        }
+        Source::Replay(replay) => replay,
+    };
+
+    let format = |d: std::time::Duration| {
+        let secs = d.as_secs();
+        format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    };
+
+    let start = replay.first().timestamp;
+
+    Some(format!(
+        "{} {} / {} at {}x",
+        if replay.playing { "▶" } else { "⏸" },
+        format(replay.current().timestamp.saturating_sub(start)),
+        format(replay.last().timestamp.saturating_sub(start)),
+        replay.speed
+    ))
 }
 
-fn split_cpus(area: Rect, _cpus: usize) -> Rc<[Rect]> {
+/// Splits the area between the cpu chart and bars, giving the bars more room when there
+/// are many cpus.
+fn split_cpus(area: Rect, cpus: usize) -> Rc<[Rect]> {
+    let chart = if cpus > MAX_PLOTTED_CPUS { 40 } else { 60 };
+
     Layout::default()
         .margin(0)
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .constraints([
+            Constraint::Percentage(chart),
+            Constraint::Percentage(100 - chart),
+        ])
         .split(area)
 }
+
+/// Width of the battery pane next to the cpu panes.
+const BATTERY_WIDTH: u16 = 36;
+
+/// Width of the pressure pane next to the disks pane, fitting its table.
+const PRESSURE_WIDTH: u16 = 48;
+
+/// With more cpus than this, only their average and the busiest ones are plotted.
+pub const MAX_PLOTTED_CPUS: usize = 16;
+
+/// How many of the busiest cpus are plotted next to the average.
+const TOP_PLOTTED_CPUS: usize = 4;
+
+/// Whether each of the named series of the pane's chart is shown.
+fn visible(app: &App, pane: Pane, names: &[impl AsRef<str>]) -> Vec<bool> {
+    names
+        .iter()
+        .map(|name| !app.is_hidden(pane, name.as_ref()))
+        .collect()
+}
+
+/// The series of every cpu with their names, or of their average and the cpus with the
+/// highest values at the cursor if there are too many to tell apart. Hidden cpus are left
+/// out of the busiest ones.
+fn plotted_cpus(
+    series: &[VecDeque<f64>],
+    cursor: usize,
+    hidden: &dyn Fn(&str) -> bool,
+) -> (Vec<VecDeque<f64>>, Vec<String>) {
+    if series.len() <= MAX_PLOTTED_CPUS {
+        let names = (0..series.len()).map(|i| format!("cpu{i}")).collect();
+        return (series.to_vec(), names);
+    }
+
+    let len = series[0].len();
+    let index = len - 1 - cursor.min(len - 1);
+
+    let mut top = (0..series.len())
+        .filter(|c| !hidden(&format!("cpu{c}")))
+        .collect::<Vec<_>>();
+    top.sort_by(|&c1, &c2| series[c2][index].total_cmp(&series[c1][index]));
+    top.truncate(TOP_PLOTTED_CPUS);
+
+    let names = std::iter::once("avg".to_string())
+        .chain(top.iter().map(|c| format!("cpu{c}")))
+        .collect::<Vec<_>>();
+
+    (named_cpus(series, &names), names)
+}
+
+/// The series of the cpus named by [`plotted_cpus`], "avg" being their average.
+fn named_cpus(series: &[VecDeque<f64>], names: &[String]) -> Vec<VecDeque<f64>> {
+    let len = series.first().map_or(0, VecDeque::len);
+
+    names
+        .iter()
+        .map(
+            |name| match name.strip_prefix("cpu").map(str::parse::<usize>) {
+                Some(Ok(cpu)) => series.get(cpu).cloned().unwrap_or_default(),
+                _ => (0..len)
+                    .map(|i| series.iter().map(|s| s[i]).sum::<f64>() / series.len() as f64)
+                    .collect(),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Series of `cpus` cpus over two samples, cpu `i` at `i` percent and then at `100 - i`.
+    fn series(cpus: usize) -> Vec<VecDeque<f64>> {
+        (0..cpus)
+            .map(|i| VecDeque::from([i as f64, 100.0 - i as f64]))
+            .collect()
+    }
+
+    #[test]
+    fn plotted() {
+        let shown = |_: &str| false;
+
+        // cpus, cursor, names, which are left out when every cpu is plotted
+        let table: [(usize, usize, &[&str]); 6] = [
+            (4, 0, &[]),
+            (16, 0, &[]),
+            (17, 0, &["avg", "cpu0", "cpu1", "cpu2", "cpu3"]),
+            (64, 0, &["avg", "cpu0", "cpu1", "cpu2", "cpu3"]),
+            // the busiest at the cursor rather than now
+            (64, 1, &["avg", "cpu63", "cpu62", "cpu61", "cpu60"]),
+            (128, 5, &["avg", "cpu127", "cpu126", "cpu125", "cpu124"]),
+        ];
+
+        for (cpus, cursor, names) in table {
+            let series = series(cpus);
+            let (plotted, plotted_names) = plotted_cpus(&series, cursor, &shown);
+
+            if cpus <= MAX_PLOTTED_CPUS {
+                let every = (0..cpus).map(|i| format!("cpu{i}")).collect::<Vec<_>>();
+                assert_eq!((plotted, plotted_names), (series, every));
+                continue;
+            }
+
+            assert_eq!(plotted_names, names, "{cpus} cpus");
+            let average = (cpus - 1) as f64 / 2.0;
+            assert_eq!(plotted[0], [average, 100.0 - average], "{cpus} cpus");
+            for (plotted, name) in plotted[1..].iter().zip(&names[1..]) {
+                let cpu = name["cpu".len()..].parse::<usize>().unwrap();
+                assert_eq!(*plotted, series[cpu]);
+            }
+        }
+    }
+
+    #[test]
+    fn hidden_cpus_make_room() {
+        let hidden = |name: &str| ["cpu1", "cpu3"].contains(&name);
+        let (_, names) = plotted_cpus(&series(32), 0, &hidden);
+        assert_eq!(names, ["avg", "cpu0", "cpu2", "cpu4", "cpu5"]);
+
+        // hiding is up to the chart below the limit
+        let (plotted, _) = plotted_cpus(&series(8), 0, &hidden);
+        assert_eq!(plotted.len(), 8);
+    }
+
+    #[test]
+    fn split() {
+        let area = Rect::new(0, 0, 100, 20);
+
+        for (cpus, chart, bars) in [(4, 60, 40), (16, 60, 40), (17, 40, 60), (128, 40, 60)] {
+            let split = split_cpus(area, cpus);
+            assert_eq!(
+                [split[0].width, split[1].width],
+                [chart, bars],
+                "{cpus} cpus"
+            );
+            assert_eq!(split[1].x, chart);
+        }
+    }
+}
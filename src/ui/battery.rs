@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use tui::{
+    prelude::*,
+    widgets::{Block, Gauge, Paragraph, Widget},
+};
+
+use crate::{
+    app::{App, ChargeState, Pane, PowerInfo},
+    config::BarsTheme,
+};
+
+use super::{chart_wrapper::ChartWrapper, cpus_bars::usage_color};
+
+/// Formats a duration like "3h05m", or "42m" under an hour.
+fn format_remaining(remaining: Duration) -> String {
+    let minutes = remaining.as_secs() / 60;
+
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// What the batteries are doing, like "discharging, 3h05m left, 9.8W".
+fn describe(power: &PowerInfo) -> String {
+    let state = match (power.state, power.ac_online) {
+        (ChargeState::Unknown, Some(true)) => "on ac",
+        (state, _) => state.name(),
+    };
+    let mut parts = vec![state.to_string()];
+
+    match (power.state, power.time_remaining) {
+        (ChargeState::Discharging, Some(remaining)) => {
+            parts.push(format!("{} left", format_remaining(remaining)))
+        }
+        (ChargeState::Charging, Some(remaining)) => {
+            parts.push(format!("{} to full", format_remaining(remaining)))
+        }
+        _ => {}
+    }
+
+    if let Some(watts) = power.power.filter(|&w| w > 0.0) {
+        parts.push(format!("{watts:.1}W"));
+    }
+
+    parts.join(", ")
+}
+
+/// The charge of the batteries as a gauge above a line describing them and a chart of the
+/// charge.
+pub struct Battery<'a, 'b> {
+    power: PowerInfo,
+    chart: ChartWrapper<'a, 'b>,
+    style: Style,
+    block: Option<Block<'a>>,
+    theme: BarsTheme,
+}
+
+impl<'a, 'b> Battery<'a, 'b> {
+    /// `None` on systems without batteries.
+    pub fn new(app: &'a App) -> Option<Self> {
+        let history = app.displayed_history();
+        let power = history.power?;
+
+        let chart = ChartWrapper::new(
+            std::slice::from_ref(&history.battery),
+            Box::new(|charge, _| format!("charge: {charge:.0}%")),
+            &app.config,
+        )
+        .range([0.0, 100.0])
+        .envelope(history, |h| vec![h.battery.clone()])
+        .visible(super::visible(app, Pane::Battery, &["charge"]))
+        .options(app.config.charts.battery)
+        .axis_top(app.axis_tops.get(Pane::Battery))
+        .history(history)
+        .cursor(app.cursor(), super::cursor_label(app));
+
+        Some(Self {
+            power,
+            chart,
+            style: Default::default(),
+            block: Default::default(),
+            theme: app.config.theme.bars,
+        })
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self {
+            chart: self.chart.style(style),
+            style,
+            ..self
+        }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        Self {
+            block: Some(block),
+            ..self
+        }
+    }
+}
+
+impl<'a, 'b> Widget for Battery<'a, 'b> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+
+        let area = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .split(area);
+
+        let charge = self.power.charge;
+        Gauge::default()
+            .label(format!("{charge:.0}%"))
+            // an emptying battery is as worrying as a filling cpu
+            .gauge_style(Style::default().fg(usage_color(&self.theme, 100.0 - charge)))
+            .ratio((charge / 100.0).clamp(0.0, 1.0))
+            .render(layout[0], buf);
+
+        Paragraph::new(describe(&self.power))
+            .alignment(Alignment::Center)
+            .style(self.style)
+            .render(layout[1], buf);
+
+        self.chart.render(layout[2], buf);
+    }
+}
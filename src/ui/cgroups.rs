@@ -0,0 +1,180 @@
+use tui::{
+    prelude::*,
+    widgets::{block::Title, Block, Cell, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crate::{
+    app::{App, CgroupInfo, MemPrefix, Pane},
+    cgroups::{CgroupColumn, CgroupRow},
+    config::{Action, BarsTheme, KeyBindings, TableTheme},
+    ui::processes::SortDirection,
+};
+
+use super::{cpus_bars::usage_color, processes::title_with_key};
+
+/// The columns after the tree, in the order they're shown, with their widths.
+const COLUMNS: &[(CgroupColumn, &str, u16)] = &[
+    (CgroupColumn::Cpu, "cpu", 7),
+    (CgroupColumn::Memory, "mem", 13),
+    (CgroupColumn::IoRead, "io r/s", 7),
+    (CgroupColumn::IoWrite, "io w/s", 7),
+    (CgroupColumn::Pids, "pids", 5),
+];
+
+fn sort_action(column: CgroupColumn) -> Action {
+    match column {
+        CgroupColumn::Name => Action::SortName,
+        CgroupColumn::Cpu => Action::SortCpu,
+        CgroupColumn::Memory => Action::SortMemory,
+        CgroupColumn::IoRead => Action::SortDiskRead,
+        CgroupColumn::IoWrite => Action::SortDiskWrite,
+        CgroupColumn::Pids => Action::SortPid,
+    }
+}
+
+/// The name of a group indented by its depth, after a marker telling whether it's collapsed.
+fn tree_name(row: &CgroupRow) -> String {
+    let marker = match (row.has_children, row.collapsed) {
+        (false, _) => " ",
+        (true, false) => "▾",
+        (true, true) => "▸",
+    };
+
+    format!("{}{marker} {}", "  ".repeat(row.depth), row.info.name())
+}
+
+/// Memory used, and the limit if there is one.
+fn mem(info: &CgroupInfo) -> String {
+    let current = MemPrefix::best_string(info.mem as f64);
+
+    match info.mem_max {
+        Some(max) => format!("{current}/{}", MemPrefix::best_string(max as f64)),
+        None => current,
+    }
+}
+
+/// The cgroup v2 hierarchy as a tree, with the children of every group sorted among
+/// themselves.
+pub struct Cgroups<'a> {
+    /// The cells of every shown group.
+    rows: Vec<(String, &'a CgroupInfo)>,
+    selected: Option<usize>,
+    column: CgroupColumn,
+    direction: SortDirection,
+    style: Style,
+    block: Option<Block<'a>>,
+    bars: BarsTheme,
+    theme: TableTheme,
+    keys: KeyBindings,
+}
+
+impl<'a> Cgroups<'a> {
+    pub fn new(app: &'a App) -> Self {
+        let rows = app.cgroups.rows(&app.displayed_history().cgroups);
+        let selected = (app.focus == Pane::Cgroups).then(|| app.cgroups.selected_row(&rows));
+
+        Self {
+            rows: rows.iter().map(|row| (tree_name(row), row.info)).collect(),
+            selected,
+            column: app.cgroups.column,
+            direction: app.cgroups.direction,
+            style: Default::default(),
+            block: Default::default(),
+            bars: app.config.theme.bars,
+            theme: app.config.theme.table,
+            keys: app.config.keys.clone(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        Self {
+            block: Some(block),
+            ..self
+        }
+    }
+
+    fn title(&self, column: CgroupColumn, title: &'static str) -> Line<'static> {
+        let arrow = match (column == self.column, self.direction) {
+            (false, _) => "",
+            (true, SortDirection::Ascending) => "▲",
+            (true, SortDirection::Descending) => "▼",
+        };
+
+        title_with_key(title, arrow, sort_action(column), &self.keys)
+    }
+}
+
+impl<'a> Widget for Cgroups<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = std::iter::once(self.title(CgroupColumn::Name, "name"))
+            .chain(
+                COLUMNS
+                    .iter()
+                    .map(|&(column, title, _)| self.title(column, title)),
+            )
+            .collect::<Vec<_>>();
+
+        let row_style = Style::default().fg(*self.theme.row_color);
+        let rows = self.rows.iter().map(|(name, info)| {
+            let mem_style = match info.mem_max {
+                Some(max) if max > 0 => Style::default().fg(usage_color(
+                    &self.bars,
+                    info.mem as f64 / max as f64 * 100.0,
+                )),
+                _ => row_style,
+            };
+
+            Row::new([
+                Cell::from(name.clone()),
+                Cell::from(format!("{:.1}%", info.cpu)),
+                Cell::from(mem(info)).style(mem_style),
+                Cell::from(MemPrefix::best_string(info.io_r as f64)),
+                Cell::from(MemPrefix::best_string(info.io_w as f64)),
+                Cell::from(info.pids.to_string()),
+            ])
+            .style(row_style)
+        });
+
+        let bottom_title = format!(
+            " press {} to collapse ",
+            self.keys.hint(Action::ToggleCgroup)
+        );
+        let block = self
+            .block
+            .unwrap_or_default()
+            .title(Title::from(bottom_title).position(tui::widgets::block::Position::Bottom));
+
+        let widths = COLUMNS.iter().map(|&(_, _, width)| width);
+        // the tree gets whatever the other columns and the spacing leave
+        let name_width = block
+            .inner(area)
+            .width
+            .saturating_sub(widths.clone().sum::<u16>() + COLUMNS.len() as u16)
+            .max(8);
+        let widths = std::iter::once(name_width)
+            .chain(widths)
+            .map(Constraint::Length)
+            .collect::<Vec<_>>();
+
+        let table = Table::new(rows)
+            .column_spacing(1)
+            .widths(&widths)
+            .block(block)
+            .style(self.style)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .header(
+                Row::new(header).style(
+                    Style::default()
+                        .fg(*self.theme.header_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            );
+
+        let mut state = TableState::default().with_selected(self.selected);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
@@ -1,25 +1,312 @@
-use std::collections::VecDeque;
+use std::{cell::Cell, collections::VecDeque, time::Duration};
 
 use tui::{
-    layout::{Alignment, Constraint},
-    style::Style,
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
     symbols::Marker,
     text::Span,
-    widgets::{Axis, Block, Chart, Dataset, GraphType, Widget},
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Widget},
 };
 
 use crate::{
-    app::HISTORY_LEN,
-    config::{Config, PlotTheme},
+    app::{History, Pane, HISTORY_LEN},
+    config::{ChartOptions, Config, LegendPosition, PlotTheme, StatsDisplay},
 };
 
+/// What the values of a chart measure, used to label the y axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    symbol: &'static str,
+    /// The prefix of every power of `base`, the first one for unprefixed values.
+    prefixes: &'static [&'static str],
+    base: f64,
+    /// What a plotted value is worth in `symbol`.
+    multiplier: f64,
+}
+
+impl Unit {
+    pub const PERCENT: Unit = Unit {
+        symbol: "%",
+        prefixes: &[""],
+        base: 1000.0,
+        multiplier: 1.0,
+    };
+
+    /// Bytes, prefixed in powers of 1024 like memory usually is.
+    pub const BYTES: Unit = Unit {
+        symbol: "",
+        prefixes: &["B", "K", "M", "G", "T", "P"],
+        base: 1024.0,
+        multiplier: 1.0,
+    };
+
+    /// Megabytes per second, prefixed in powers of 1000 like disk throughput usually is.
+    pub const MEGABYTES_PER_SECOND: Unit = Unit {
+        symbol: "/s",
+        prefixes: &["B", "KB", "MB", "GB", "TB", "PB"],
+        base: 1000.0,
+        multiplier: 1_000_000.0,
+    };
+
+    /// Events per second, like disk reads.
+    pub const PER_SECOND: Unit = Unit {
+        symbol: "/s",
+        prefixes: &["", "k", "M", "G"],
+        base: 1000.0,
+        multiplier: 1.0,
+    };
+
+    pub const MILLISECONDS: Unit = Unit {
+        symbol: "s",
+        prefixes: &["m", ""],
+        base: 1000.0,
+        multiplier: 1.0,
+    };
+
+    pub const MEGAHERTZ: Unit = Unit {
+        symbol: "Hz",
+        prefixes: &["", "K", "M", "G", "T"],
+        base: 1000.0,
+        multiplier: 1_000_000.0,
+    };
+
+    /// The prefix that keeps `value` readable, together with what a plotted value has to be
+    /// divided by to be expressed in it.
+    fn prefix(&self, value: f64) -> (f64, &'static str) {
+        let mut scaled = value.abs() * self.multiplier;
+        let mut divisor = 1.0 / self.multiplier;
+        let mut prefixes = self.prefixes.iter().peekable();
+
+        while let Some(prefix) = prefixes.next() {
+            if scaled < self.base || prefixes.peek().is_none() {
+                return (divisor, prefix);
+            }
+
+            scaled /= self.base;
+            divisor *= self.base;
+        }
+
+        (divisor, "")
+    }
+
+    /// Formats a plotted value, with a single decimal if it isn't close to a whole number.
+    fn format(&self, value: f64) -> String {
+        let (divisor, prefix) = self.prefix(value);
+        let value = value / divisor;
+
+        if (value - value.round()).abs() < 0.05 {
+            format!("{value:.0}{prefix}{}", self.symbol)
+        } else {
+            format!("{value:.1}{prefix}{}", self.symbol)
+        }
+    }
+}
+
+/// Where the top of a y axis scaled to the data was drawn last, so that it can ease down
+/// after a spike instead of jumping with every sample.
+#[derive(Debug, Default)]
+pub struct AxisTop(Cell<Option<DrawnTop>>);
+
+#[derive(Debug, Clone, Copy)]
+struct DrawnTop {
+    top: f64,
+    unit: Unit,
+    /// Timestamp of the newest sample at the time.
+    newest: Option<Duration>,
+}
+
+impl AxisTop {
+    /// The top of the axis for data reaching up to `max`. It follows the data up right
+    /// away, but down only by `decay` percent for every new sample.
+    fn follow(&self, max: f64, unit: Unit, newest: Option<Duration>, decay: u32) -> f64 {
+        let top = match self.0.get() {
+            // a different view of the pane starts over
+            Some(drawn) if drawn.unit != unit => max,
+            Some(drawn) if drawn.newest == newest => drawn.top.max(max),
+            Some(drawn) => max.max(drawn.top * (1.0 - decay.min(100) as f64 / 100.0)),
+            None => max,
+        };
+
+        self.0.set(Some(DrawnTop { top, unit, newest }));
+        top
+    }
+}
+
+/// The axis tops of every pane with a chart.
+#[derive(Debug, Default)]
+pub struct AxisTops {
+    cpu: AxisTop,
+    battery: AxisTop,
+    mem: AxisTop,
+    disks: AxisTop,
+}
+
+impl AxisTops {
+    pub fn get(&self, pane: Pane) -> Option<&AxisTop> {
+        match pane {
+            Pane::Cpu => Some(&self.cpu),
+            Pane::Battery => Some(&self.battery),
+            Pane::Memory => Some(&self.mem),
+            Pane::Disks => Some(&self.disks),
+            Pane::Processes | Pane::Filesystems | Pane::Pressure | Pane::Cgroups => None,
+        }
+    }
+}
+
+/// The largest amount of labelled ticks on the y axis, not counting the bottom.
+const MAX_TICKS: usize = 5;
+
+/// The smallest of 1, 2, 2.5 and 5 times a power of ten that divides `span` into at most
+/// [`MAX_TICKS`] steps, or 1 for an empty span.
+fn nice_step(span: f64) -> f64 {
+    if span <= 0.0 {
+        return 1.0;
+    }
+
+    let raw = span / MAX_TICKS as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Formats how long ago a sample was taken, like "-90s" or "-5m".
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs_f64().round() as u64;
+
+    match secs {
+        0 => "now".into(),
+        1..=119 => format!("-{secs}s"),
+        120..=7199 => format!("-{}m", secs / 60),
+        7200..=172_799 => format!("-{}h", secs / 3600),
+        _ => format!("-{}d", secs / 86400),
+    }
+}
+
+/// Minimum, average, maximum and 95th percentile of a series.
+struct Stats {
+    min: f64,
+    avg: f64,
+    max: f64,
+    p95: f64,
+}
+
+impl Stats {
+    fn new(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_by(f64::total_cmp);
+        let p95 = (values.len() as f64 * 0.95).ceil() as usize;
+
+        Some(Self {
+            min: values[0],
+            avg: values.iter().sum::<f64>() / values.len() as f64,
+            max: values[values.len() - 1],
+            p95: values[p95.max(1) - 1],
+        })
+    }
+
+    fn values(&self) -> [f64; 4] {
+        [self.min, self.avg, self.max, self.p95]
+    }
+}
+
+/// Moves a value onto the y axis. A logarithmic axis is linear up to 1 and `1 + log10(value)`
+/// above, so that zero stays at the bottom and every power of ten lands on a whole position.
+fn scale(value: f64, log_scale: bool) -> f64 {
+    match value {
+        _ if !log_scale => value,
+        ..=1.0 => value.max(0.0),
+        _ => 1.0 + value.log10(),
+    }
+}
+
+/// The value at a position of a logarithmic axis, undoing [`scale`].
+fn unscale(position: f64) -> f64 {
+    if position <= 1.0 {
+        position
+    } else {
+        10f64.powf(position - 1.0)
+    }
+}
+
+/// Points covering the area between the bottom of the chart and the curve, dense enough
+/// for every dot of a braille character in an area of the given size.
+fn fill(data: &[(f64, f64)], bottom: f64, width: u16, height: u16) -> Vec<(f64, f64)> {
+    let (Some(&(first, _)), Some(&(last, _))) = (data.first(), data.last()) else {
+        return Vec::new();
+    };
+
+    let columns = (width as usize * 2).max(2);
+    let rows = (height as usize * 4).max(1);
+    let top = data.iter().map(|p| p.1).fold(bottom, f64::max);
+    let step = (top - bottom) / rows as f64;
+
+    let mut points = Vec::new();
+    for column in 0..columns {
+        let x = first + (last - first) * column as f64 / (columns - 1) as f64;
+
+        // the value at x, interpolated between the neighbouring samples
+        let i = data
+            .partition_point(|p| p.0 < x)
+            .clamp(1, data.len().max(2) - 1);
+        let value = match (data.get(i - 1), data.get(i)) {
+            (Some(&(x0, y0)), Some(&(x1, y1))) if x1 > x0 => y0 + (y1 - y0) * (x - x0) / (x1 - x0),
+            (Some(&(_, y)), _) => y,
+            _ => bottom,
+        };
+
+        let start = points.len();
+        let mut y = bottom;
+        while y < value && step > 0.0 {
+            points.push((x, y));
+            y += step;
+        }
+        points.push((x, value));
+
+        // snaking up and down keeps the lines connecting the points inside the area
+        if column % 2 == 1 {
+            points[start..].reverse();
+        }
+    }
+
+    points
+}
+
+/// The points of every series of a chart.
+type Series = Vec<Vec<(f64, f64)>>;
+
 pub struct ChartWrapper<'a, 'b> {
-    data: Vec<Vec<(f64, f64)>>,
+    data: Series,
+    /// The lowest and highest samples behind every point of every series, when zoomed out.
+    envelope: Option<[Series; 2]>,
     style: Style,
     block: Option<Block<'b>>,
     label_generator: Box<dyn Fn(f64, usize) -> String + 'a>,
-    range: [f64; 2],
-    label_suffix: Option<char>,
+    /// The bounds of the y axis, scaled to the data if `None`.
+    range: Option<[f64; 2]>,
+    unit: Unit,
+    /// Time between samples, used to label the x axis.
+    interval: Option<Duration>,
+    /// How many samples every series has.
+    len: usize,
+    /// How many of the newest samples were collected rather than padding.
+    collected: usize,
+    /// Timestamp of the newest sample.
+    newest: Option<Duration>,
+    /// Where the top of the axis was drawn before, if it is kept.
+    axis_top: Option<&'a AxisTop>,
+    stacked: bool,
+    cursor: Option<(usize, String)>,
+    /// Whether every series is shown, all of them are if it is shorter than the data.
+    visible: Vec<bool>,
+    options: ChartOptions,
     theme: PlotTheme,
 }
 
@@ -27,26 +314,27 @@ impl<'a, 'b> ChartWrapper<'a, 'b> {
     pub fn new(
         data: &[VecDeque<f64>],
         label_generator: Box<dyn Fn(f64, usize) -> String + 'a>,
-        range: [f64; 2],
         config: &Config,
     ) -> Self {
-        let data = data
-            .iter()
-            .map(|cpu| {
-                (0..HISTORY_LEN)
-                    .map(|x| x as f64)
-                    .zip(cpu.iter().copied())
-                    .collect()
-            })
-            .collect();
+        let len = data.iter().map(VecDeque::len).max().unwrap_or(HISTORY_LEN);
 
         Self {
-            data,
+            data: points(data, len),
+            envelope: None,
             style: Style::default(),
             block: None,
             label_generator,
-            range,
-            label_suffix: None,
+            range: None,
+            unit: Unit::PERCENT,
+            interval: None,
+            len,
+            collected: len,
+            newest: None,
+            axis_top: None,
+            stacked: false,
+            cursor: None,
+            visible: Vec::new(),
+            options: ChartOptions::default(),
             theme: config.theme.plot.clone(),
         }
     }
@@ -62,74 +350,707 @@ impl<'a, 'b> ChartWrapper<'a, 'b> {
         }
     }
 
-    pub fn label_suffix(self, label_suffix: char) -> Self {
+    /// Fixes the bounds of the y axis instead of scaling it to the data.
+    pub fn range(self, range: [f64; 2]) -> Self {
+        Self {
+            range: Some(range),
+            ..self
+        }
+    }
+
+    pub fn unit(self, unit: Unit) -> Self {
+        Self { unit, ..self }
+    }
+
+    /// Labels the x axis with the age of the samples of the history, and leaves the padding
+    /// before the first collected sample out of the statistics.
+    pub fn history(self, history: &History) -> Self {
+        Self {
+            interval: history.interval(),
+            collected: history.collected(),
+            newest: history.timestamps.back().copied(),
+            ..self
+        }
+    }
+
+    /// Draws the lowest and highest samples behind every point around the series and takes
+    /// the statistics' minimum and maximum from them, if the history is of a zoomed out tier.
+    /// `series` splits a history up into the plotted series.
+    pub fn envelope(
+        self,
+        history: &History,
+        series: impl Fn(&History) -> Vec<VecDeque<f64>>,
+    ) -> Self {
+        let envelope = history
+            .bounds
+            .as_deref()
+            .map(|bounds| bounds.each_ref().map(|b| points(&series(b), self.len)));
+        Self { envelope, ..self }
+    }
+
+    /// Keeps the top of an axis scaled to the data in `axis_top` between frames, easing it
+    /// down by the decay of the options.
+    pub fn axis_top(self, axis_top: Option<&'a AxisTop>) -> Self {
+        Self { axis_top, ..self }
+    }
+
+    /// Marks every series as the sum of the series before it and its own values, so that
+    /// statistics are computed over its own values only.
+    pub fn stacked(self) -> Self {
         Self {
-            label_suffix: Some(label_suffix),
+            stacked: true,
             ..self
         }
     }
+
+    /// Shows only the series whose entry is true.
+    pub fn visible(self, visible: Vec<bool>) -> Self {
+        Self { visible, ..self }
+    }
+
+    /// Sets the marker, graph type, fill and scale the chart is drawn with.
+    pub fn options(self, options: ChartOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Marks the sample `offset` samples before the newest one with a vertical line
+    /// described by `label`, and shows values from that sample in the legend.
+    pub fn cursor(self, offset: Option<usize>, label: impl Into<String>) -> Self {
+        Self {
+            cursor: offset.map(|o| (o, label.into())),
+            ..self
+        }
+    }
+
+    /// The bounds of the y axis in plotted values, and the values labelled on it from the
+    /// bottom up.
+    fn y_ticks(&self) -> ([f64; 2], Vec<f64>) {
+        if let Some([bottom, top]) = self.range.filter(|_| !self.options.log_scale) {
+            let ticks = (0..=MAX_TICKS)
+                .map(|k| bottom + (top - bottom) * k as f64 / MAX_TICKS as f64)
+                .collect();
+            return ([bottom, top], ticks);
+        }
+
+        let max = match self.range {
+            Some([_, top]) => top,
+            None => {
+                let highest = self
+                    .envelope
+                    .as_ref()
+                    .map_or(&self.data, |[_, highest]| highest);
+                let max = self
+                    .data
+                    .iter()
+                    .chain(highest)
+                    .flatten()
+                    .map(|p| p.1)
+                    .fold(0.0, f64::max);
+                let max = max * (1.0 + self.options.headroom as f64 / 100.0);
+
+                match self.axis_top {
+                    Some(axis_top) => {
+                        axis_top.follow(max, self.unit, self.newest, self.options.axis_decay)
+                    }
+                    None => max,
+                }
+            }
+        };
+
+        if self.options.log_scale {
+            // a tick every `decades` whole positions, which are all powers of ten but the
+            // bottom one
+            let magnitude = scale(max, true).ceil().max(1.0) as usize;
+            let decades = magnitude.div_ceil(MAX_TICKS);
+            let ticks = magnitude.div_ceil(decades);
+
+            let values = (0..=ticks).map(|k| unscale((k * decades) as f64)).collect();
+            return ([0.0, (ticks * decades) as f64], values);
+        }
+
+        // nice steps in the unit the largest value is labelled in
+        let (divisor, _) = self.unit.prefix(max);
+        let step = if max > 0.0 {
+            nice_step(max / divisor) * divisor
+        } else {
+            divisor
+        };
+        let ticks = ((max / step).ceil() as usize).max(1);
+
+        let values = (0..=ticks).map(|k| k as f64 * step).collect();
+        ([0.0, ticks as f64 * step], values)
+    }
 }
 
 impl<'a, 'b> Widget for ChartWrapper<'a, 'b> {
-    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+    fn render(self, area: Rect, buf: &mut Buffer) {
         let colors = self.theme.plot_colors.iter().cycle();
+        let cursor = self.cursor.as_ref().map(|(offset, _)| *offset).unwrap_or(0);
+        let ChartOptions {
+            marker,
+            graph,
+            fill: filled,
+            log_scale,
+            time_labels,
+            ..
+        } = self.options;
 
-        let datasets = self
+        let (range, ticks) = self.y_ticks();
+
+        let scaled = |data: &[(f64, f64)]| {
+            data.iter()
+                .map(|&(x, y)| (x, scale(y, log_scale)))
+                .collect::<Vec<_>>()
+        };
+
+        let plotted = self
             .data
             .iter()
-            .zip(colors)
-            .enumerate()
-            .map(|(i, (data, &color))| {
+            .map(|data| {
+                if filled {
+                    fill(&scaled(data), range[0], area.width, area.height)
+                } else {
+                    scaled(data)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the lowest and highest samples of every series, a filled area would cover them
+        let envelope = match &self.envelope {
+            Some([lowest, highest]) if !filled => lowest
+                .iter()
+                .zip(highest)
+                .map(|(lowest, highest)| [scaled(lowest), scaled(highest)])
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // the series that are shown with their legend entries and statistics, in the order of
+        // the data
+        let mut legend = Vec::new();
+        let mut stats = Vec::new();
+        let mut datasets = Vec::new();
+        for (i, ((data, plotted), &color)) in self.data.iter().zip(&plotted).zip(colors).enumerate()
+        {
+            if !self.visible.get(i).copied().unwrap_or(true) {
+                continue;
+            }
+
+            let value = data
+                .len()
+                .checked_sub(cursor + 1)
+                .map(|i| data[i].1)
+                .unwrap_or_default();
+            let style = Style::default().fg(*color);
+
+            legend.push(((self.label_generator)(value, i), style));
+            stats.push(self.stats(i));
+            for bound in envelope.get(i).into_iter().flatten() {
+                datasets.push(
+                    Dataset::default()
+                        .data(bound)
+                        .graph_type(graph.graph_type())
+                        .marker(marker.marker())
+                        .style(style.add_modifier(Modifier::DIM)),
+                );
+            }
+
+            datasets.push(
                 Dataset::default()
-                    .data(data)
+                    .data(plotted)
+                    .graph_type(graph.graph_type())
+                    .marker(marker.marker())
+                    .style(style),
+            );
+        }
+
+        // later datasets are drawn over earlier ones, so the largest areas have to come first
+        // for stacked series to stay visible
+        if filled {
+            datasets.reverse();
+        }
+
+        let axis_label_style = Style::default().fg(*self.theme.axis_labels_color);
+
+        let cursor_line = self.cursor.as_ref().map(|(offset, _)| {
+            let x = self.len.saturating_sub(offset + 1) as f64;
+            [(x, range[0]), (x, range[1])]
+        });
+
+        if let (Some(cursor_line), Some((_, label))) = (&cursor_line, &self.cursor) {
+            legend.push((label.clone(), axis_label_style));
+            datasets.push(
+                Dataset::default()
+                    .data(cursor_line)
                     .graph_type(GraphType::Line)
                     .marker(Marker::Braille)
-                    .name((self.label_generator)(data.last().unwrap().1, i))
-                    .style(Style::default().fg(*color))
-            })
-            .collect();
+                    .style(axis_label_style),
+            );
+        }
 
-        let label_suffix = self.label_suffix.map(String::from).unwrap_or_default();
+        // the bottom is left unlabelled, it is always zero
+        let y_labels = std::iter::once(Span::styled("", axis_label_style))
+            .chain(
+                ticks
+                    .iter()
+                    .skip(1)
+                    .map(|&value| Span::styled(self.unit.format(value), axis_label_style)),
+            )
+            .collect::<Vec<_>>();
+        let mut labels_width = y_labels.iter().map(Span::width).max().unwrap_or_default();
 
-        let axis_label_style = Style::default().fg(*self.theme.axis_labels_color);
+        let newest = self.len.saturating_sub(1) as f64;
+        let mut x_axis = Axis::default().bounds([0.0, newest]);
+        let interval = self.interval.filter(|_| time_labels);
+        if let Some(interval) = interval {
+            let oldest = interval * self.len.saturating_sub(1) as u32;
+            let oldest_label = format_age(oldest);
+            // the oldest label sticks out to the left of the y axis
+            labels_width = labels_width.max(oldest_label.len().saturating_sub(1));
+
+            x_axis = x_axis
+                .labels(vec![
+                    Span::styled(oldest_label, axis_label_style),
+                    Span::styled(format_age(oldest / 2), axis_label_style),
+                    Span::styled(format_age(Duration::ZERO), axis_label_style),
+                ])
+                .labels_alignment(Alignment::Left);
+        }
 
+        // the legends of the datasets are drawn below instead, as they have no names
         let mut chart = Chart::new(datasets)
-            .x_axis(Axis::default().bounds([0.0, HISTORY_LEN as f64]))
+            .x_axis(x_axis)
             .y_axis(
                 Axis::default()
-                    .bounds(self.range)
-                    .labels(vec![
-                        Span::styled("", axis_label_style),
-                        Span::styled(
-                            format!("{:.0}{label_suffix}", self.range[1] / 5.0),
-                            axis_label_style,
-                        ),
-                        Span::styled(
-                            format!("{:.0}{label_suffix}", self.range[1] * 2.0 / 5.0),
-                            axis_label_style,
-                        ),
-                        Span::styled(
-                            format!("{:.0}{label_suffix}", self.range[1] * 3.0 / 5.0),
-                            axis_label_style,
-                        ),
-                        Span::styled(
-                            format!("{:.0}{label_suffix}", self.range[1] * 4.0 / 5.0),
-                            axis_label_style,
-                        ),
-                        Span::styled(
-                            format!("{:.0}{label_suffix}", self.range[1]),
-                            axis_label_style,
-                        ),
-                    ])
+                    .bounds(range)
+                    .labels(y_labels)
                     .labels_alignment(Alignment::Right),
             )
-            .hidden_legend_constraints((Constraint::Percentage(75), Constraint::Percentage(75)))
             .style(self.style);
 
-        if let Some(block) = self.block {
+        let inner = self.block.as_ref().map_or(area, |block| block.inner(area));
+        if let Some(block) = self.block.clone() {
             chart = chart.block(block);
         }
 
         chart.render(area, buf);
+
+        // where the chart draws the data, next to the axes and their labels
+        let left = (labels_width as u16).min(inner.width / 3) + 1;
+        let bottom = if interval.is_some() { 2 } else { 0 };
+        let graph = Rect {
+            x: inner.x + left,
+            y: inner.y,
+            width: inner.width.saturating_sub(left),
+            height: inner.height.saturating_sub(bottom),
+        };
+
+        let cursor_entry = self.cursor.is_some().then(|| legend.pop()).flatten();
+        let mut position = self.options.legend;
+
+        match self.options.stats {
+            StatsDisplay::Hidden => {}
+
+            StatsDisplay::Legend => {
+                for ((name, _), stats) in legend.iter_mut().zip(&stats) {
+                    if let Some(stats) = stats {
+                        let [min, avg, max, p95] = stats.values().map(|v| self.unit.format(v));
+                        name.push_str(&format!("  min {min} avg {avg} max {max} p95 {p95}"));
+                    }
+                }
+            }
+
+            StatsDisplay::Table => {
+                legend = self.stats_table(legend, &stats);
+                if position == LegendPosition::Hidden {
+                    position = LegendPosition::TopRight;
+                }
+            }
+        }
+
+        legend.extend(cursor_entry);
+        render_legend(&legend, position, graph, buf);
+    }
+}
+
+impl<'a, 'b> ChartWrapper<'a, 'b> {
+    /// Statistics over the collected samples of a series.
+    fn stats(&self, series: usize) -> Option<Stats> {
+        let start = self.len.saturating_sub(self.collected);
+        let below = series
+            .checked_sub(1)
+            .filter(|_| self.stacked)
+            .map(|below| &self.data[below]);
+
+        let values = self.data[series]
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(x, &(_, y))| y - below.map_or(0.0, |below| below[x].1))
+            .collect();
+
+        let mut stats = Stats::new(values)?;
+
+        // the averages of a zoomed out tier hide the extremes of the samples behind them
+        if let Some([lowest, highest]) = self.envelope.as_ref().filter(|_| !self.stacked) {
+            let collected = |bound: &[Vec<(f64, f64)>]| {
+                bound
+                    .get(series)
+                    .into_iter()
+                    .flat_map(|data| data.iter().skip(start).map(|&(_, y)| y))
+                    .collect::<Vec<_>>()
+            };
+            stats.min = collected(lowest).into_iter().fold(stats.min, f64::min);
+            stats.max = collected(highest).into_iter().fold(stats.max, f64::max);
+        }
+
+        Some(stats)
+    }
+
+    /// Legend entries laid out as a table with a column for every statistic.
+    fn stats_table(
+        &self,
+        legend: Vec<(String, Style)>,
+        stats: &[Option<Stats>],
+    ) -> Vec<(String, Style)> {
+        let rows = legend
+            .into_iter()
+            .zip(stats)
+            .map(|((name, style), stats)| {
+                let values = match stats {
+                    Some(stats) => stats.values().map(|v| self.unit.format(v)),
+                    None => Default::default(),
+                };
+                (name, values, style)
+            })
+            .collect::<Vec<_>>();
+
+        let header = ["min", "avg", "max", "p95"].map(String::from);
+        let name_width = rows.iter().map(|r| r.0.len()).max().unwrap_or_default();
+        let widths = (0..header.len()).map(|column| {
+            rows.iter()
+                .map(|r| &r.1[column])
+                .chain([&header[column]])
+                .map(String::len)
+                .max()
+                .unwrap_or_default()
+        });
+        let widths = widths.collect::<Vec<_>>();
+
+        let format_row = |name: &str, values: &[String; 4]| {
+            let mut row = format!("{name:name_width$}");
+            for (value, width) in values.iter().zip(&widths) {
+                row.push_str(&format!("  {value:>width$}"));
+            }
+            row
+        };
+
+        std::iter::once((
+            format_row("", &header),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+        .chain(
+            rows.iter()
+                .map(|(name, values, style)| (format_row(name, values), *style)),
+        )
+        .collect()
+    }
+}
+
+/// Every series as points at their index, at most `len` of them.
+fn points(data: &[VecDeque<f64>], len: usize) -> Series {
+    data.iter()
+        .map(|series| {
+            (0..len)
+                .map(|x| x as f64)
+                .zip(series.iter().copied())
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws the names of the series in a box in a corner of the graph. Series that don't fit
+/// are summed up in the last row.
+fn render_legend(
+    entries: &[(String, Style)],
+    position: LegendPosition,
+    graph: Rect,
+    buf: &mut Buffer,
+) {
+    // the box and at least one entry have to fit
+    if position == LegendPosition::Hidden || entries.is_empty() || graph.height < 3 {
+        return;
+    }
+
+    let rows = (graph.height - 2) as usize;
+    let (shown, more) = if entries.len() > rows {
+        let shown = rows - 1;
+        (
+            &entries[..shown],
+            Some(format!("+{} more", entries.len() - shown)),
+        )
+    } else {
+        (entries, None)
+    };
+
+    let text_width = shown
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .chain(more.as_ref().map(|m| m.len()))
+        .max()
+        .unwrap_or_default() as u16;
+    let width = (text_width + 2).min(graph.width);
+    let height = (shown.len() + usize::from(more.is_some())) as u16 + 2;
+    if width < 3 {
+        return;
+    }
+
+    let x = match position {
+        LegendPosition::TopLeft | LegendPosition::BottomLeft => graph.x,
+        _ => graph.right() - width,
+    };
+    let y = match position {
+        LegendPosition::BottomLeft | LegendPosition::BottomRight => graph.bottom() - height,
+        _ => graph.y,
+    };
+    let legend = Rect::new(x, y, width, height);
+
+    Clear.render(legend, buf);
+    Block::default().borders(Borders::ALL).render(legend, buf);
+
+    let lines = shown
+        .iter()
+        .map(|(name, style)| (name.as_str(), *style))
+        .chain(more.as_deref().map(|more| (more, Style::default())));
+    for (row, (name, style)) in lines.enumerate() {
+        buf.set_stringn(
+            legend.x + 1,
+            legend.y + 1 + row as u16,
+            name,
+            width as usize - 2,
+            style,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn nice_steps() {
+        assert_eq!(nice_step(0.0), 1.0);
+        assert_close(nice_step(5.0), 1.0);
+        assert_close(nice_step(7.0), 2.0);
+        assert_close(nice_step(12.0), 2.5);
+        assert_close(nice_step(100.0), 20.0);
+        assert_close(nice_step(3.0), 1.0);
+        assert_close(nice_step(0.3), 0.1);
+        assert_close(nice_step(0.02), 0.005);
+    }
+
+    #[test]
+    fn ages() {
+        let age = |secs: u64| format_age(Duration::from_secs(secs));
+
+        assert_eq!(age(0), "now");
+        assert_eq!(format_age(Duration::from_millis(400)), "now");
+        assert_eq!(age(1), "-1s");
+        assert_eq!(age(119), "-119s");
+        assert_eq!(age(120), "-2m");
+        assert_eq!(age(7199), "-119m");
+        assert_eq!(age(7200), "-2h");
+        assert_eq!(age(172_799), "-47h");
+        assert_eq!(age(172_800), "-2d");
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(Unit::PERCENT.format(0.0), "0%");
+        assert_eq!(Unit::PERCENT.format(42.44), "42.4%");
+        assert_eq!(Unit::PERCENT.format(99.97), "100%");
+        assert_eq!(Unit::BYTES.format(0.5), "0.5B");
+        assert_eq!(Unit::BYTES.format(1536.0), "1.5K");
+        assert_eq!(Unit::BYTES.format(1024.0 * 1024.0), "1M");
+        assert_eq!(Unit::MEGABYTES_PER_SECOND.format(0.5), "500KB/s");
+        assert_eq!(Unit::MEGABYTES_PER_SECOND.format(2.5), "2.5MB/s");
+        assert_eq!(Unit::MEGABYTES_PER_SECOND.format(0.0), "0B/s");
+        assert_eq!(Unit::MILLISECONDS.format(0.5), "0.5ms");
+        assert_eq!(Unit::MILLISECONDS.format(1500.0), "1.5s");
+        assert_eq!(Unit::MEGAHERTZ.format(2400.0), "2.4GHz");
+    }
+
+    #[test]
+    fn fill_geometry() {
+        assert!(fill(&[], 0.0, 10, 10).is_empty());
+
+        // a ramp from 0 to 2 in an area 5 cells wide and 2 high, so 10 columns of 8 dots
+        let points = fill(&[(0.0, 0.0), (10.0, 2.0)], 0.0, 5, 2);
+        let columns = points.chunk_by(|p1, p2| p1.0 == p2.0).collect::<Vec<_>>();
+        assert_eq!(columns.len(), 10);
+
+        for (column, points) in columns.iter().enumerate() {
+            let x = points[0].0;
+            assert_eq!(x, 10.0 * column as f64 / 9.0);
+
+            // odd columns are drawn from the top down
+            let mut ys = points.iter().map(|p| p.1).collect::<Vec<_>>();
+            if column % 2 == 1 {
+                ys.reverse();
+            }
+
+            // from the bottom up to the curve without skipping a dot
+            assert_eq!(ys[0], 0.0);
+            assert!((ys[ys.len() - 1] - x / 5.0).abs() < 1e-9);
+            assert!(ys
+                .windows(2)
+                .all(|w| w[1] > w[0] && w[1] - w[0] <= 0.25 + 1e-9));
+        }
+
+        // a single sample still fills a column
+        let points = fill(&[(3.0, 1.0)], 0.0, 1, 1);
+        assert!(points
+            .iter()
+            .all(|&(x, y)| x == 3.0 && (0.0..=1.0).contains(&y)));
+        assert!(points.contains(&(3.0, 1.0)));
+    }
+
+    #[test]
+    fn log_ticks() {
+        let ticks = |max: f64| {
+            ChartWrapper::new(
+                &[VecDeque::from([0.0, max])],
+                Box::new(|_, _| String::new()),
+                &Config::default(),
+            )
+            .options(ChartOptions {
+                log_scale: true,
+                headroom: 0,
+                ..Default::default()
+            })
+            .y_ticks()
+        };
+
+        let (range, values) = ticks(100.0);
+        assert_eq!(range, [0.0, 3.0]);
+        assert_eq!(values, [0.0, 1.0, 10.0, 100.0]);
+        assert_eq!(scale(100.0, true), range[1]);
+
+        let (range, values) = ticks(0.5);
+        assert_eq!(range, [0.0, 1.0]);
+        assert_eq!(values, [0.0, 1.0]);
+
+        // above five decades the labels skip some
+        let (range, values) = ticks(1e9);
+        assert_eq!(range, [0.0, 10.0]);
+        assert_eq!(values, [0.0, 10.0, 1e3, 1e5, 1e7, 1e9]);
+
+        // labels are spaced evenly, so every labelled value has to be plotted there
+        for (range, values) in [ticks(3.0), ticks(42_000.0), ticks(1e9), ticks(1e14)] {
+            let n = values.len() - 1;
+            for (k, &value) in values.iter().enumerate() {
+                let position = range[1] * k as f64 / n as f64;
+                assert!((scale(value, true) - position).abs() < 1e-9);
+            }
+        }
+        assert_eq!(
+            [0.0, 0.5, 1.0, 10.0, 1000.0].map(|v| unscale(scale(v, true))),
+            [0.0, 0.5, 1.0, 10.0, 1000.0]
+        );
+    }
+
+    #[test]
+    fn axis_decay() {
+        use crate::collector::Snapshot;
+
+        let axis_top = AxisTop::default();
+        let top = |max: f64, secs: u64, unit: Unit| {
+            let mut history = History::with_len(1, 2);
+            history.push(Snapshot {
+                timestamp: Duration::from_secs(secs),
+                cpus: vec![max],
+                ..Default::default()
+            });
+
+            ChartWrapper::new(
+                &history.cpus,
+                Box::new(|_, _| String::new()),
+                &Config::default(),
+            )
+            .unit(unit)
+            .options(ChartOptions {
+                headroom: 0,
+                axis_decay: 50,
+                ..Default::default()
+            })
+            .history(&history)
+            .axis_top(Some(&axis_top))
+            .y_ticks()
+            .0[1]
+        };
+
+        assert_eq!(top(100.0, 1, Unit::PER_SECOND), 100.0);
+        // redrawing without a new sample keeps the axis
+        assert_eq!(top(10.0, 1, Unit::PER_SECOND), 100.0);
+        // which then halves with every sample until it meets the data
+        assert_eq!(top(10.0, 2, Unit::PER_SECOND), 50.0);
+        assert_eq!(top(10.0, 3, Unit::PER_SECOND), 25.0);
+        assert_eq!(top(10.0, 4, Unit::PER_SECOND), 12.5);
+        assert_eq!(top(10.0, 5, Unit::PER_SECOND), 10.0);
+        // but follows rising data right away
+        assert_eq!(top(40.0, 6, Unit::PER_SECOND), 40.0);
+        // and starts over in another view
+        assert_eq!(top(1.0, 6, Unit::MILLISECONDS), 1.0);
+    }
+
+    #[test]
+    fn stats() {
+        assert!(Stats::new(Vec::new()).is_none());
+
+        let single = Stats::new(vec![7.0]).unwrap();
+        assert_eq!(single.values(), [7.0; 4]);
+
+        // the 95th percentile is the value at ceil(n * 0.95) - 1 once sorted
+        let twenty = Stats::new((1..=20).rev().map(f64::from).collect()).unwrap();
+        assert_eq!(twenty.values(), [1.0, 10.5, 20.0, 19.0]);
+
+        let ten = Stats::new((1..=10).map(f64::from).collect()).unwrap();
+        assert_eq!(ten.values(), [1.0, 5.5, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn zoomed_stats() {
+        use crate::collector::Snapshot;
+
+        let mut history = History::with_len(1, 4);
+        let mut bounds = [history.clone(), history.clone()];
+        for (secs, [min, avg, max]) in [(1, [1.0, 2.0, 3.0]), (2, [0.5, 4.0, 9.0]), (3, [2.0; 3])] {
+            let snapshot = |cpu| Snapshot {
+                timestamp: Duration::from_secs(secs),
+                cpus: vec![cpu],
+                ..Default::default()
+            };
+            history.push(snapshot(avg));
+            bounds[0].push(snapshot(min));
+            bounds[1].push(snapshot(max));
+        }
+        history.bounds = Some(Box::new(bounds));
+
+        let chart = ChartWrapper::new(
+            &history.cpus,
+            Box::new(|_, _| String::new()),
+            &Config::default(),
+        )
+        .history(&history)
+        .envelope(&history, |h| h.cpus.clone());
+
+        // the extremes come from the samples, the average and percentile from the points
+        assert_eq!(chart.stats(0).unwrap().values(), [0.5, 8.0 / 3.0, 9.0, 4.0]);
+        // and the axis makes room for the highest sample
+        assert!(chart.y_ticks().0[1] >= 9.0);
     }
 }
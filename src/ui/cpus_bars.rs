@@ -1,32 +1,73 @@
 use tui::{
-    layout::{Constraint, Direction, Layout},
-    style::Style,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
     widgets::{Block, Borders, Gauge, Widget},
 };
 
-use crate::{app::App, config::BarsTheme};
+use crate::{
+    app::{App, CpuTimes, CpuView},
+    config::BarsTheme,
+};
+
+use super::heatmap::gradient;
+
+/// Formats a frequency in MHz, switching to GHz from 1000 MHz on.
+pub(super) fn format_frequency(mhz: f64) -> String {
+    if mhz >= 1000.0 {
+        format!("{:.2}GHz", mhz / 1000.0)
+    } else {
+        format!("{mhz:.0}MHz")
+    }
+}
 
 pub struct CpusBars<'a> {
     cpus: Vec<f64>,
+    frequencies: Vec<f64>,
+    /// The time categories of all cpus together followed by every cpu, when they're
+    /// shown instead of the usage.
+    breakdown: Option<Vec<(String, CpuTimes)>>,
     style: Style,
     block: Option<Block<'a>>,
     theme: BarsTheme,
+    breakdown_colors: Vec<Color>,
 }
 
 impl<'a> CpusBars<'a> {
     pub fn new(app: &App) -> Self {
-        let cpus = app
-            .cpu_history
-            .iter()
-            .flat_map(|v| v.back())
-            .copied()
-            .collect();
+        let history = app.displayed_history();
+        let index = history.index(app.cursor().unwrap_or_default());
+        let cpus = history.cpus.iter().map(|v| v[index]).collect();
+        let frequencies = history.frequencies.iter().map(|v| v[index]).collect();
+
+        let breakdown =
+            (app.cpu_view == CpuView::Breakdown && !history.cpu_times.is_empty()).then(|| {
+                std::iter::once(("all".to_string(), history.total_cpu_times[index]))
+                    .chain(
+                        history
+                            .cpu_times
+                            .iter()
+                            .enumerate()
+                            .map(|(i, times)| (format!("cpu{i}"), times[index])),
+                    )
+                    .collect()
+            });
 
         Self {
             cpus,
+            frequencies,
+            breakdown,
             style: Default::default(),
             block: Default::default(),
             theme: app.config.theme.bars,
+            breakdown_colors: app
+                .config
+                .theme
+                .plot
+                .plot_colors
+                .iter()
+                .map(|c| c.0)
+                .collect(),
         }
     }
 
@@ -42,10 +83,72 @@ impl<'a> CpusBars<'a> {
     }
 }
 
+impl<'a> CpusBars<'a> {
+    /// Renders a line for every entry of the breakdown, with a segment per time category.
+    /// Entries that don't fit are counted on the last line instead.
+    fn render_breakdown(&self, breakdown: &[(String, CpuTimes)], area: Rect, buf: &mut Buffer) {
+        if area.width < 2 || area.height == 0 {
+            return;
+        }
+
+        let shown = if breakdown.len() > area.height as usize {
+            area.height as usize - 1
+        } else {
+            breakdown.len()
+        };
+
+        let label_width = breakdown.iter().map(|(l, _)| l.len()).max().unwrap_or(0) as u16;
+        // room for the label, the spaces around the bar and the total
+        let bar_width = area.width.saturating_sub(label_width + 9);
+
+        for ((label, times), y) in breakdown[..shown].iter().zip(area.top()..area.bottom()) {
+            buf.set_stringn(
+                area.x + 1,
+                y,
+                label,
+                area.width.saturating_sub(1) as usize,
+                self.style,
+            );
+
+            let mut x = area.x + label_width + 2;
+            let mut filled = 0.0;
+            for (value, color) in times
+                .values()
+                .into_iter()
+                .zip(self.breakdown_colors.iter().cycle())
+            {
+                filled += value;
+                let end =
+                    area.x + label_width + 2 + (filled / 100.0 * bar_width as f64).round() as u16;
+
+                while x < end.min(area.x + label_width + 2 + bar_width) {
+                    buf.get_mut(x, y).set_symbol("█").set_fg(*color);
+                    x += 1;
+                }
+            }
+
+            // the total is left out when the pane is too narrow for it
+            let total = format!("{filled:>5.1}%");
+            let total_x = area.x + label_width + 3 + bar_width;
+            if total_x + total.len() as u16 <= area.right() {
+                buf.set_string(total_x, y, total, self.style);
+            }
+        }
+
+        if shown < breakdown.len() {
+            buf.set_stringn(
+                area.x + 1,
+                area.bottom() - 1,
+                format!("{} more", breakdown.len() - shown),
+                area.width.saturating_sub(1) as usize,
+                self.style,
+            );
+        }
+    }
+}
+
 impl<'a> Widget for CpusBars<'a> {
     fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
-        let vertical_amount = (self.cpus.len() as u32 + 3) / 4;
-
         buf.set_style(area, self.style);
 
         let area = match self.block.clone() {
@@ -57,54 +160,250 @@ impl<'a> Widget for CpusBars<'a> {
             None => area,
         };
 
-        let mut constraints = vec![Constraint::Max(3); vertical_amount as usize];
-        constraints.push(Constraint::Min(0));
-
-        let rows = Layout::default()
-            .margin(0)
-            .direction(Direction::Vertical)
-            .constraints(constraints)
-            .split(area);
-
-        let cells = rows
-            .iter()
-            .map(|&row| {
-                Layout::default()
-                    .margin(0)
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Ratio(1, 4); 4])
-                    .split(row)
-            })
-            .collect::<Vec<_>>();
+        if let Some(breakdown) = &self.breakdown {
+            self.render_breakdown(breakdown, area, buf);
+            return;
+        }
+
+        let amount = self.cpus.len();
+
+        // labels only make room for a frequency if there is one to show
+        let frequency_width = if self.frequencies.iter().any(|&mhz| mhz > 0.0) {
+            FREQUENCY_WIDTH
+        } else {
+            0
+        };
+
+        match layout(area, amount, frequency_width) {
+            (BarsLayout::Gauges, cells) => self.render_gauges(&cells, buf),
+            (BarsLayout::Compact, cells) => self.render_compact(&cells, buf),
+            (BarsLayout::Heatmap, cells) => self.render_heatmap(&cells, buf),
+        }
+    }
+}
+
+/// How the cpus are drawn, from the roomiest to the most compact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BarsLayout {
+    Gauges,
+    Compact,
+    Heatmap,
+}
+
+/// The roomiest layout fitting every cpu into the area, with a cell for each of them. Cpus
+/// that don't even fit into a heatmap are left out.
+fn layout(area: Rect, amount: usize, frequency_width: u16) -> (BarsLayout, Vec<Rect>) {
+    if let Some(cells) = grid(area, amount, GAUGE_WIDTH + frequency_width, 3) {
+        (BarsLayout::Gauges, cells)
+    } else if let Some(cells) = grid(area, amount, COMPACT_WIDTH + frequency_width, 1) {
+        (BarsLayout::Compact, cells)
+    } else {
+        let cells = grid(area, amount, 2, 1).unwrap_or_else(|| {
+            let columns = (area.width / 2).max(1);
+            cells(area, columns, area.height, 2, 1)
+        });
+        (BarsLayout::Heatmap, cells)
+    }
+}
+
+/// Width of a gauge with a label like `cpu127: 100.00%` and its padding.
+const GAUGE_WIDTH: u16 = 18;
+
+/// Width of a single line bar with a label like `127 100%`.
+const COMPACT_WIDTH: u16 = 10;
+
+/// Width a frequency like ` 2.40GHz` adds to a label.
+const FREQUENCY_WIDTH: u16 = 8;
 
+/// Splits the area into cells at least `width` wide and exactly `height` high, one for
+/// each of `amount` cpus. `None` if they don't fit.
+fn grid(area: Rect, amount: usize, width: u16, height: u16) -> Option<Vec<Rect>> {
+    let columns = (area.width / width).clamp(1, amount.max(1) as u16);
+    let rows = (amount as u16).div_ceil(columns);
+
+    (rows * height <= area.height && columns * width <= area.width).then(|| {
+        let mut cells = cells(area, columns, rows, area.width / columns, height);
+        cells.truncate(amount);
         cells
-            .iter()
-            .flat_map(|c| c.iter())
-            .copied()
-            .zip(self.cpus)
-            .enumerate()
-            .for_each(|(i, (area, val))| {
-                let color = if val < 50.0 {
-                    *self.theme.low_usage_color
-                } else if val < 80.0 {
-                    *self.theme.medium_usage_color
-                } else {
-                    *self.theme.high_usage_color
-                };
-
-                Gauge::default()
-                    .label(format!("cpu{i}: {val:.2}%"))
-                    .gauge_style(Style::default().fg(color))
-                    .ratio(val / 100.0)
-                    .block(Block::default().borders(Borders::empty()).padding(
-                        tui::widgets::Padding {
+    })
+}
+
+/// `columns` times `rows` cells of the given size, row by row.
+fn cells(area: Rect, columns: u16, rows: u16, width: u16, height: u16) -> Vec<Rect> {
+    (0..rows)
+        .flat_map(|row| {
+            (0..columns).map(move |column| Rect {
+                x: area.x + column * width,
+                y: area.y + row * height,
+                width,
+                height,
+            })
+        })
+        .filter(|cell| cell.bottom() <= area.bottom())
+        .collect()
+}
+
+fn last_in_row(cells: &[Rect], i: usize) -> bool {
+    cells.get(i + 1).is_none_or(|next| next.y != cells[i].y)
+}
+
+/// The color of a usage in percent, going from low to high usage.
+pub(super) fn usage_color(theme: &BarsTheme, usage: f64) -> Color {
+    if usage < 50.0 {
+        *theme.low_usage_color
+    } else if usage < 80.0 {
+        *theme.medium_usage_color
+    } else {
+        *theme.high_usage_color
+    }
+}
+
+impl<'a> CpusBars<'a> {
+    /// `label` followed by the frequency of cpu `i`, if it is known.
+    fn with_frequency(&self, label: String, i: usize) -> String {
+        match self.frequencies.get(i).filter(|&&mhz| mhz > 0.0) {
+            Some(&mhz) => format!("{label} {}", format_frequency(mhz)),
+            None => label,
+        }
+    }
+
+    /// Gauges labeled with the usage and frequency.
+    fn render_gauges(&self, cells: &[Rect], buf: &mut Buffer) {
+        for (i, (&area, &val)) in cells.iter().zip(&self.cpus).enumerate() {
+            Gauge::default()
+                .label(self.with_frequency(format!("cpu{i}: {val:.2}%"), i))
+                .gauge_style(Style::default().fg(usage_color(&self.theme, val)))
+                .ratio((val / 100.0).clamp(0.0, 1.0))
+                .block(
+                    Block::default()
+                        .borders(Borders::empty())
+                        .padding(tui::widgets::Padding {
                             left: 1,
-                            right: if i % 4 == 3 { 1 } else { 0 },
+                            right: if last_in_row(cells, i) { 1 } else { 0 },
                             top: 1,
                             bottom: 1,
-                        },
-                    ))
-                    .render(area, buf);
-            });
+                        }),
+                )
+                .render(area, buf);
+        }
+    }
+
+    /// Single line gauges labeled with just the cpu number, rounded usage and frequency.
+    fn render_compact(&self, cells: &[Rect], buf: &mut Buffer) {
+        for (i, (&area, &val)) in cells.iter().zip(&self.cpus).enumerate() {
+            let area = Rect {
+                width: area.width.saturating_sub(1),
+                ..area
+            };
+
+            Gauge::default()
+                .label(self.with_frequency(format!("{i} {val:.0}%"), i))
+                .gauge_style(Style::default().fg(usage_color(&self.theme, val)))
+                .ratio((val / 100.0).clamp(0.0, 1.0))
+                .render(area, buf);
+        }
+    }
+
+    /// A colored block for every cpu, for when there are too many for any labels.
+    fn render_heatmap(&self, cells: &[Rect], buf: &mut Buffer) {
+        for (area, &val) in cells.iter().zip(&self.cpus) {
+            buf.get_mut(area.x, area.y)
+                .set_symbol("█")
+                .set_fg(gradient(&self.theme, val));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts() {
+        let small = Rect::new(0, 0, 40, 12);
+        let large = Rect::new(0, 0, 120, 40);
+        let tiny = Rect::new(5, 5, 10, 3);
+
+        // cpus, area, frequency shown, layout, cells drawn, cell width
+        let table = [
+            (4, small, false, BarsLayout::Gauges, 4, 20),
+            (4, small, true, BarsLayout::Gauges, 4, 40),
+            (16, small, false, BarsLayout::Compact, 16, 10),
+            (16, small, true, BarsLayout::Compact, 16, 20),
+            (64, small, false, BarsLayout::Heatmap, 64, 2),
+            (128, small, true, BarsLayout::Heatmap, 128, 2),
+            (16, large, true, BarsLayout::Gauges, 16, 30),
+            (64, large, false, BarsLayout::Gauges, 64, 20),
+            (128, large, false, BarsLayout::Compact, 128, 10),
+            (128, large, true, BarsLayout::Compact, 128, 20),
+            // only as many as fit into the area
+            (128, tiny, false, BarsLayout::Heatmap, 15, 2),
+            (3, Rect::new(0, 0, 2, 1), false, BarsLayout::Heatmap, 1, 2),
+        ];
+
+        for (cpus, area, frequency, expected, drawn, width) in table {
+            let frequency_width = if frequency { FREQUENCY_WIDTH } else { 0 };
+            let (layout, cells) = layout(area, cpus, frequency_width);
+            let case = format!("{cpus} cpus in {area:?}, frequency {frequency}");
+
+            assert_eq!(layout, expected, "{case}");
+            assert_eq!(cells.len(), drawn, "{case}");
+            assert!(cells.iter().all(|cell| cell.width == width), "{case}");
+            assert!(cells.iter().all(|cell| area.union(*cell) == area), "{case}");
+            assert!(
+                cells
+                    .iter()
+                    .enumerate()
+                    .all(|(i, cell)| cells[..i].iter().all(|other| !other.intersects(*cell))),
+                "{case}"
+            );
+        }
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(format_frequency(800.0), "800MHz");
+        assert_eq!(format_frequency(999.4), "999MHz");
+        assert_eq!(format_frequency(1000.0), "1.00GHz");
+        assert_eq!(format_frequency(2456.0), "2.46GHz");
+
+        let bars = CpusBars {
+            cpus: vec![12.5, 100.0, 50.0],
+            // the frequency of the last cpu isn't known
+            frequencies: vec![2400.0, 800.0, 0.0],
+            breakdown: None,
+            style: Default::default(),
+            block: None,
+            theme: Default::default(),
+            breakdown_colors: Vec::new(),
+        };
+
+        let labels = (0..3)
+            .map(|i| bars.with_frequency(format!("cpu{i}"), i))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["cpu0 2.40GHz", "cpu1 800MHz", "cpu2"]);
+        // and labels with a frequency fit into their cells
+        let widest = format!("cpu127: {:.2}%", 100.0);
+        assert!(
+            bars.with_frequency(widest, 0).len() <= (GAUGE_WIDTH + FREQUENCY_WIDTH) as usize - 2
+        );
+        assert!(
+            bars.with_frequency("127 100%".into(), 0).len()
+                < (COMPACT_WIDTH + FREQUENCY_WIDTH) as usize
+        );
+    }
+
+    #[test]
+    fn cells_row_by_row() {
+        let cells = cells(Rect::new(2, 1, 10, 2), 3, 3, 3, 1);
+
+        // the third row falls below the area
+        assert_eq!(
+            cells.iter().map(|c| (c.x, c.y)).collect::<Vec<_>>(),
+            [(2, 1), (5, 1), (8, 1), (2, 2), (5, 2), (8, 2)]
+        );
+        assert!(last_in_row(&cells, 2));
+        assert!(!last_in_row(&cells, 3));
+        assert!(last_in_row(&cells, 5));
     }
 }
@@ -5,16 +5,23 @@ use tui::{
 
 use crate::app::App;
 
+#[cfg(not(target_os = "windows"))]
+use std::collections::VecDeque;
+
+#[cfg(not(target_os = "windows"))]
+use crate::app::{DiskInfo, DiskView, History, Pane};
+
 #[cfg(target_os = "windows")]
 use tui::widgets::Paragraph;
 
 #[cfg(not(target_os = "windows"))]
-use super::chart_wrapper::ChartWrapper;
+use super::chart_wrapper::{ChartWrapper, Unit};
 
 #[cfg(not(target_os = "windows"))]
-fn to_mb(sectors: usize) -> f64 {
-    // FIXME: some disks have sector size != 512
-    sectors as f64 * 512.0 / 1_000_000.0
+fn megabytes_per_second(info: &DiskInfo) -> Vec<f64> {
+    info.bytes_per_second()
+        .map(|bytes| bytes / 1_000_000.0)
+        .to_vec()
 }
 
 pub struct Disks<'a, 'b> {
@@ -30,43 +37,76 @@ pub struct Disks<'a, 'b> {
 
 impl<'a, 'b> Disks<'a, 'b> {
     #[cfg(not(target_os = "windows"))]
-    pub fn new(app: &App) -> Self {
-        let data = app
+    pub fn new(app: &'a App) -> Self {
+        let history = app.displayed_history();
+        let index = history.index(app.cursor().unwrap_or_default());
+        let names = history.disks.keys().cloned().collect::<Vec<_>>();
+
+        // the read and write series of every disk, or a single one while showing utilization
+        let values: fn(&DiskInfo) -> Vec<f64> = match app.disk_view {
+            DiskView::Throughput => megabytes_per_second,
+            DiskView::Iops => |info| info.iops().to_vec(),
+            DiskView::Latency => |info| info.await_ms().to_vec(),
+            DiskView::Utilization => |info| vec![info.utilization()],
+        };
+        let series = if app.disk_view == DiskView::Utilization {
+            1
+        } else {
+            2
+        };
+
+        let data = |history: &History| {
+            history
+                .disks
+                .values()
+                .flat_map(|q| {
+                    let mut data = vec![VecDeque::with_capacity(q.len()); series];
+
+                    for info in q.iter() {
+                        for (data, value) in data.iter_mut().zip(values(info)) {
+                            data.push_back(value);
+                        }
+                    }
+
+                    data
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let name = move |i: usize| match series {
+            1 => names[i].clone(),
+            _ => format!("{} {}", names[i / 2], ["r", "w"][i % 2]),
+        };
+        let queue_depths = history
             .disks
             .values()
-            .flat_map(|(_, q)| {
-                let mut r = Vec::with_capacity(q.len());
-                let mut w = Vec::with_capacity(q.len());
-
-                for info in q.iter() {
-                    r.push(to_mb(info.r_sectors));
-                    w.push(to_mb(info.w_sectors));
-                }
-
-                [r.into(), w.into()]
-            })
+            .map(|q| q[index].queue_depth())
             .collect::<Vec<_>>();
 
-        let &max = data
-            .iter()
-            .flatten()
-            .max_by(|&&f1: &&f64, &f2| f1.total_cmp(f2))
-            .unwrap_or(&1.0);
-        let names = app.disks.keys().cloned().collect::<Vec<_>>();
-
-        let chart = ChartWrapper::new(
-            &data,
-            Box::new(move |v, i| {
-                format!(
-                    "{} {}: {v:.02}M/s",
-                    names[i / 2],
-                    if i % 2 == 0 { "r" } else { "w" }
-                )
-            }),
-            [0.0, max],
-            &app.config,
-        )
-        .label_suffix('M');
+        let label_generator: Box<dyn Fn(f64, usize) -> String> = match app.disk_view {
+            DiskView::Throughput => Box::new(move |v, i| format!("{}: {v:.02}MB/s", name(i))),
+            DiskView::Iops => Box::new(move |v, i| format!("{}: {v:.0}/s", name(i))),
+            DiskView::Latency => Box::new(move |v, i| format!("{}: {v:.1}ms", name(i))),
+            DiskView::Utilization => {
+                Box::new(move |v, i| format!("{}: {v:.1}% (queue {:.2})", name(i), queue_depths[i]))
+            }
+        };
+
+        let visible = super::visible(app, Pane::Disks, &app.series(Pane::Disks));
+
+        let chart =
+            ChartWrapper::new(&data(history), label_generator, &app.config).envelope(history, data);
+        let chart = match app.disk_view {
+            DiskView::Throughput => chart.unit(Unit::MEGABYTES_PER_SECOND),
+            DiskView::Iops => chart.unit(Unit::PER_SECOND),
+            DiskView::Latency => chart.unit(Unit::MILLISECONDS),
+            DiskView::Utilization => chart.range([0.0, 100.0]),
+        }
+        .visible(visible)
+        .options(app.config.charts.disks)
+        .axis_top(app.axis_tops.get(Pane::Disks))
+        .history(history)
+        .cursor(app.cursor(), super::cursor_label(app));
 
         Self { chart }
     }
@@ -94,7 +134,7 @@ impl<'a, 'b> Disks<'a, 'b> {
         }
     }
 
-    pub fn block(self, block: Block<'a>) -> Disks {
+    pub fn block(self, block: Block<'a>) -> Disks<'a, 'a> {
         Disks {
             #[cfg(not(target_os = "windows"))]
             chart: self.chart.block(block),
@@ -0,0 +1,124 @@
+use tui::{
+    prelude::*,
+    widgets::{Block, Cell, Row, Table, Widget},
+};
+
+use crate::{
+    app::{App, FilesystemInfo, MemPrefix},
+    config::{BarsTheme, TableTheme},
+};
+
+use super::cpus_bars::usage_color;
+
+/// Width of the usage gauge, without the percentage after it.
+const GAUGE_WIDTH: usize = 6;
+
+/// A table of the mounted filesystems, the fullest first.
+pub struct Filesystems<'a> {
+    filesystems: Vec<FilesystemInfo>,
+    style: Style,
+    block: Option<Block<'a>>,
+    bars: BarsTheme,
+    theme: TableTheme,
+}
+
+impl<'a> Filesystems<'a> {
+    pub fn new(app: &App) -> Self {
+        // recordings and agents may have been collected without leaving them out
+        let exclude_types = &app.config.filesystems.exclude_types;
+
+        let mut filesystems = app
+            .displayed_history()
+            .filesystems
+            .iter()
+            .filter(|fs| !exclude_types.contains(&fs.fs_type))
+            .cloned()
+            .collect::<Vec<_>>();
+        filesystems.sort_by(|a, b| b.usage().total_cmp(&a.usage()));
+
+        Self {
+            filesystems,
+            style: Default::default(),
+            block: Default::default(),
+            bars: app.config.theme.bars,
+            theme: app.config.theme.table,
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> Filesystems {
+        Filesystems {
+            block: Some(block),
+            ..self
+        }
+    }
+
+    /// A bar filled up to the usage in its color, followed by the usage.
+    fn gauge(&self, usage: f64) -> Line<'static> {
+        let filled = ((usage / 100.0 * GAUGE_WIDTH as f64).round() as usize).min(GAUGE_WIDTH);
+
+        Line::from(vec![
+            Span::styled(
+                "█".repeat(filled),
+                Style::default().fg(usage_color(&self.bars, usage)),
+            ),
+            Span::raw("░".repeat(GAUGE_WIDTH - filled)),
+            Span::raw(format!("{usage:>4.0}%")),
+        ])
+    }
+}
+
+impl<'a> Widget for Filesystems<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .filesystems
+            .iter()
+            .map(|fs| {
+                let inodes = fs
+                    .inode_usage()
+                    .map_or("-".to_string(), |usage| format!("{usage:.0}%"));
+
+                Row::new([
+                    Cell::from(fs.mount_point.clone()),
+                    Cell::from(fs.fs_type.clone()),
+                    Cell::from(MemPrefix::best_string(fs.total as f64)),
+                    Cell::from(MemPrefix::best_string(fs.used as f64)),
+                    Cell::from(MemPrefix::best_string(fs.avail as f64)),
+                    Cell::from(self.gauge(fs.usage())),
+                    Cell::from(inodes),
+                ])
+                .style(Style::default().fg(*self.theme.row_color))
+            })
+            .collect::<Vec<_>>();
+
+        let block = self.block.unwrap_or_default();
+        let widths = [5, 6, 6, 6, GAUGE_WIDTH as u16 + 5, 5];
+        // the mount point gets whatever the other columns and the spacing leave
+        let mount_width = block
+            .inner(area)
+            .width
+            .saturating_sub(widths.iter().sum::<u16>() + widths.len() as u16)
+            .max(6);
+        let widths = std::iter::once(mount_width)
+            .chain(widths)
+            .map(Constraint::Length)
+            .collect::<Vec<_>>();
+
+        Table::new(rows)
+            .column_spacing(1)
+            .widths(&widths)
+            .block(block)
+            .style(self.style)
+            .header(
+                Row::new(["mount", "type", "size", "used", "avail", "use", "inode"]).style(
+                    Style::default()
+                        .fg(*self.theme.header_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .render(area, buf);
+    }
+}
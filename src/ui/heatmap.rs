@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+
+use tui::{
+    prelude::*,
+    widgets::{Block, Widget},
+};
+
+use crate::{app::App, config::BarsTheme};
+
+/// The red, green and blue components of a color, approximated for the named ones.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    Some(match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        _ => return None,
+    })
+}
+
+/// The color of a usage in percent, blending from the low to the medium usage color up to
+/// 50% and from the medium to the high usage color above.
+pub(super) fn gradient(theme: &BarsTheme, usage: f64) -> Color {
+    let usage = usage.clamp(0.0, 100.0) / 100.0;
+    let (from, to, t) = if usage < 0.5 {
+        (theme.low_usage_color, theme.medium_usage_color, usage * 2.0)
+    } else {
+        (
+            theme.medium_usage_color,
+            theme.high_usage_color,
+            usage * 2.0 - 1.0,
+        )
+    };
+
+    let (Some(from), Some(to)) = (rgb(*from), rgb(*to)) else {
+        // colors that can't be blended switch at the halfway point instead
+        return if t < 0.5 { *from } else { *to };
+    };
+
+    let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    Color::Rgb(
+        blend(from.0, to.0),
+        blend(from.1, to.1),
+        blend(from.2, to.2),
+    )
+}
+
+/// Cpus as rows and samples as columns, every cell colored by the usage.
+pub struct Heatmap<'a> {
+    cpus: Vec<VecDeque<f64>>,
+    style: Style,
+    block: Option<Block<'a>>,
+    theme: BarsTheme,
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn new(app: &App) -> Self {
+        Self {
+            cpus: app.displayed_history().cpus.clone(),
+            style: Default::default(),
+            block: Default::default(),
+            theme: app.config.theme.bars,
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> Heatmap {
+        Heatmap {
+            block: Some(block),
+            ..self
+        }
+    }
+}
+
+impl<'a> Widget for Heatmap<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+
+        let area = match self.block.clone() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        if self.cpus.is_empty() || area.height == 0 {
+            return;
+        }
+
+        // with more cpus than rows, every row shows the busiest cpu of a group
+        let per_row = self.cpus.len().div_ceil(area.height as usize);
+        let groups = self.cpus.chunks(per_row).collect::<Vec<_>>();
+
+        let labels = (0..groups.len())
+            .map(|row| {
+                let first = row * per_row;
+                let last = (first + per_row).min(self.cpus.len()) - 1;
+
+                if first == last {
+                    format!("{first}")
+                } else {
+                    format!("{first}-{last}")
+                }
+            })
+            .collect::<Vec<_>>();
+        let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(0) as u16 + 1;
+
+        let width = area.width.saturating_sub(label_width);
+        if width == 0 {
+            return;
+        }
+
+        let samples = self.cpus[0].len();
+        for ((group, label), y) in groups.iter().zip(&labels).zip(area.top()..area.bottom()) {
+            buf.set_string(
+                area.x,
+                y,
+                format!("{label:>0$}", label_width as usize - 1),
+                self.style,
+            );
+
+            for column in 0..width {
+                // the newest sample is at the right edge, stretched over the whole width
+                let sample = column as usize * samples / width as usize;
+                let usage = group.iter().map(|cpu| cpu[sample]).fold(0.0, f64::max);
+
+                buf.get_mut(area.x + label_width + column, y)
+                    .set_symbol("█")
+                    .set_fg(gradient(&self.theme, usage));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::SerdeColor;
+
+    use super::*;
+
+    #[test]
+    fn gradients() {
+        let theme = BarsTheme::default();
+
+        assert_eq!(gradient(&theme, 0.0), Color::Rgb(0, 205, 0));
+        assert_eq!(gradient(&theme, 25.0), Color::Rgb(103, 205, 0));
+        assert_eq!(gradient(&theme, 50.0), Color::Rgb(205, 205, 0));
+        assert_eq!(gradient(&theme, 75.0), Color::Rgb(205, 103, 0));
+        assert_eq!(gradient(&theme, 100.0), Color::Rgb(205, 0, 0));
+        // out of range usages are clamped
+        assert_eq!(gradient(&theme, -5.0), gradient(&theme, 0.0));
+        assert_eq!(gradient(&theme, 250.0), gradient(&theme, 100.0));
+
+        // indexed colors can't be blended
+        let indexed = BarsTheme {
+            low_usage_color: SerdeColor(Color::Indexed(1)),
+            medium_usage_color: SerdeColor(Color::Indexed(2)),
+            high_usage_color: SerdeColor(Color::Rgb(255, 255, 255)),
+        };
+        assert_eq!(gradient(&indexed, 20.0), Color::Indexed(1));
+        assert_eq!(gradient(&indexed, 30.0), Color::Indexed(2));
+        assert_eq!(gradient(&indexed, 60.0), Color::Indexed(2));
+        assert_eq!(gradient(&indexed, 100.0), Color::Rgb(255, 255, 255));
+    }
+
+    #[test]
+    fn groups_cpus_into_rows() {
+        let theme = BarsTheme::default();
+        // cpu i at 10 * i percent and then at 90 - 10 * i
+        let cpus = (0..10)
+            .map(|i| VecDeque::from([i as f64 * 10.0, 90.0 - i as f64 * 10.0]))
+            .collect();
+        let heatmap = Heatmap {
+            cpus,
+            style: Style::default(),
+            block: None,
+            theme,
+        };
+
+        let area = Rect::new(0, 0, 10, 4);
+        let mut buf = Buffer::empty(area);
+        heatmap.render(area, &mut buf);
+
+        let row = |y: u16| {
+            (0..area.width)
+                .map(|x| buf.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        let color = |x: u16, y: u16| buf.get(x, y).fg;
+
+        // 3 cpus to a row, labelled with their range
+        assert_eq!(row(0), "0-2 ██████");
+        assert_eq!(row(1), "3-5 ██████");
+        assert_eq!(row(2), "6-8 ██████");
+        assert_eq!(row(3), "  9 ██████");
+
+        // each row shows its busiest cpu, the older sample on the left half
+        for (y, [older, newer]) in [[20.0, 90.0], [50.0, 60.0], [80.0, 30.0], [90.0, 0.0]]
+            .into_iter()
+            .enumerate()
+        {
+            let y = y as u16;
+            assert_eq!(color(4, y), gradient(&theme, older));
+            assert_eq!(color(6, y), gradient(&theme, older));
+            assert_eq!(color(7, y), gradient(&theme, newer));
+            assert_eq!(color(9, y), gradient(&theme, newer));
+        }
+    }
+}
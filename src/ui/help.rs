@@ -0,0 +1,222 @@
+use tui::{
+    prelude::*,
+    widgets::{block::Title, Block, Clear, Paragraph, Widget},
+};
+
+use crate::{
+    app::App,
+    config::{Action, KeyBindings, Scope},
+};
+
+/// A popup listing every action together with the keys bound to it.
+pub struct Help<'a> {
+    keys: KeyBindings,
+    mode: Scope,
+    offset: u16,
+    style: Style,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> Help<'a> {
+    pub fn new(app: &App) -> Self {
+        Self {
+            keys: app.config.keys.clone(),
+            mode: app.mode(),
+            offset: app.help.unwrap_or_default(),
+            style: Default::default(),
+            block: Default::default(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> Help {
+        Help {
+            block: Some(block),
+            ..self
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let heading_style = Style::default().add_modifier(Modifier::BOLD);
+        let key_width = Action::ALL
+            .iter()
+            .map(|&a| self.keys.hint(a).len())
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("current mode: ", heading_style),
+                self.mode.name().into(),
+            ]),
+            Line::default(),
+        ];
+
+        for &scope in Scope::ALL {
+            let heading = if scope == self.mode {
+                format!("{} (current)", scope.name())
+            } else {
+                scope.name().to_string()
+            };
+            lines.push(Line::styled(heading, heading_style));
+
+            lines.extend(Action::ALL.iter().filter(|a| a.scope() == scope).map(|&a| {
+                let hint = self.keys.hint(a);
+                let padding = " ".repeat(key_width - hint.len() + 2);
+
+                Line::from(vec![
+                    "  ".into(),
+                    Span::styled(hint, Style::default().add_modifier(Modifier::UNDERLINED)),
+                    format!("{padding}{}", a.description()).into(),
+                ])
+            }));
+            lines.push(Line::default());
+        }
+
+        lines
+    }
+
+    /// The largest scroll offset that still shows something for the given area.
+    pub fn max_offset(app: &App, area: Rect) -> u16 {
+        let help = Self::new(app);
+        let visible = popup_area(area).height.saturating_sub(2);
+
+        (help.lines().len() as u16).saturating_sub(visible)
+    }
+}
+
+/// The part of the screen the help popup is drawn over.
+pub fn popup_area(area: Rect) -> Rect {
+    let width = (area.width * 4 / 5).max(area.width.min(40));
+    let height = (area.height * 4 / 5).max(area.height.min(10));
+
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+impl<'a> Widget for Help<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area);
+        let lines = self.lines();
+        let visible = area.height.saturating_sub(2) as usize;
+
+        let mut block = self.block.clone().unwrap_or_default();
+        if lines.len() > visible {
+            block = block.title(
+                Title::from(format!(
+                    " {}-{} of {} ",
+                    self.offset + 1,
+                    (self.offset as usize + visible).min(lines.len()),
+                    lines.len()
+                ))
+                .position(tui::widgets::block::Position::Bottom),
+            );
+        }
+
+        Clear.render(area, buf);
+
+        Paragraph::new(lines)
+            .block(block)
+            .style(self.style)
+            .scroll((self.offset, 0))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::{backend::TestBackend, Terminal};
+
+    use crate::{app::Source, collector::Snapshot, config::Config, recording::Replay};
+
+    use super::*;
+
+    fn app(keys: KeyBindings) -> App {
+        let config = Config {
+            keys,
+            ..Default::default()
+        };
+        let replay = Replay::new(vec![Snapshot::default()]);
+        App::new(config, Source::Replay(replay), None).unwrap()
+    }
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn lists_every_action() {
+        let mut keys = KeyBindings::default();
+        keys.rebind(Action::Quit, &["f12", "ctrl+q"]);
+        keys.rebind(Action::Search, &[]);
+
+        let app = app(keys.clone());
+        let lines = Help::new(&app).lines().iter().map(text).collect::<Vec<_>>();
+
+        let mode = Scope::Normal.name();
+        assert_eq!(lines[0], format!("current mode: {mode}"));
+        assert!(lines.contains(&format!("{mode} (current)")));
+
+        for &action in Action::ALL {
+            let hint = keys.hint(action);
+            assert!(
+                lines.iter().any(|line| line.trim_start().starts_with(&hint)
+                    && line.ends_with(action.description())),
+                "{action:?} isn't listed with {hint}"
+            );
+        }
+
+        let quit = lines
+            .iter()
+            .find(|line| line.ends_with(Action::Quit.description()))
+            .unwrap();
+        assert!(quit.contains("f12") && quit.contains("ctrl+q"), "{quit}");
+        let search = lines
+            .iter()
+            .find(|line| line.ends_with(Action::Search.description()))
+            .unwrap();
+        assert!(search.contains("unbound"), "{search}");
+    }
+
+    #[test]
+    fn scrolling_stops_at_the_end() {
+        let mut app = app(KeyBindings::default());
+        let area = Rect::new(0, 0, 80, 24);
+        let lines = Help::new(&app).lines();
+
+        // the popup has room for 17 lines between its borders
+        assert_eq!(popup_area(area), Rect::new(8, 2, 64, 19));
+        let max_offset = Help::max_offset(&app, area);
+        assert_eq!(max_offset as usize, lines.len() - 17);
+        assert_eq!(Help::max_offset(&app, Rect::new(0, 0, 200, 200)), 0);
+
+        // scrolled all the way down, the last action is just above the trailing blank line
+        app.help = Some(max_offset);
+        let mut buf = Buffer::empty(area);
+        Help::new(&app)
+            .block(Block::default().borders(tui::widgets::Borders::ALL))
+            .render(area, &mut buf);
+        let row = |y: u16| {
+            (9..71)
+                .map(|x| buf.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert_eq!(row(18).trim_end(), text(&lines[lines.len() - 2]));
+        assert_eq!(row(19).trim(), "");
+
+        // and drawing clamps scrolling past it
+        app.help = Some(u16::MAX);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|frame| crate::ui::render(&mut app, frame))
+            .unwrap();
+        assert_eq!(app.help, Some(max_offset));
+    }
+}
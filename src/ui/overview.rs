@@ -0,0 +1,193 @@
+use tui::{
+    prelude::*,
+    widgets::{block::Title, Block, Row, Table, Widget},
+};
+
+use crate::{
+    app::MemPrefix,
+    config::{Action, Config, KeyBindings, Theme},
+    hosts::{Host, HostColumn, Hosts},
+    ui::processes::SortDirection,
+};
+
+use super::processes::title_with_key;
+
+/// How many of the latest samples the cpu history column shows.
+pub(super) const SPARKLINE_LEN: usize = 16;
+
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The overview columns, in the order they're shown.
+const COLUMNS: &[(Option<HostColumn>, &str)] = &[
+    (Some(HostColumn::Name), "name"),
+    (Some(HostColumn::Cpu), "cpu"),
+    (Some(HostColumn::Memory), "mem"),
+    (Some(HostColumn::Load), "load"),
+    (None, "top process"),
+    (Some(HostColumn::DiskRead), "disk r/s"),
+    (Some(HostColumn::DiskWrite), "disk w/s"),
+    (None, "cpu history"),
+];
+
+fn sort_action(column: HostColumn) -> Action {
+    match column {
+        HostColumn::Name => Action::SortName,
+        HostColumn::Cpu => Action::SortCpu,
+        HostColumn::Memory => Action::SortMemory,
+        HostColumn::Load => Action::SortLoad,
+        HostColumn::DiskRead => Action::SortDiskRead,
+        HostColumn::DiskWrite => Action::SortDiskWrite,
+    }
+}
+
+/// Renders the latest values as a line of block characters, a full block being `max`.
+pub(super) fn sparkline(history: &[f64], max: f64) -> String {
+    history[history.len().saturating_sub(SPARKLINE_LEN)..]
+        .iter()
+        .map(|&value| {
+            let level = (value / max * SPARKLINE_BARS.len() as f64) as usize;
+            SPARKLINE_BARS[level.min(SPARKLINE_BARS.len() - 1)]
+        })
+        .collect()
+}
+
+/// The cells of a host no snapshot was received from, with `status` in the widest column.
+fn unavailable(host: &Host, status: &str) -> Vec<String> {
+    vec![
+        host.name.clone(),
+        "-".into(),
+        "-".into(),
+        "-".into(),
+        status.into(),
+    ]
+}
+
+fn cells(host: &Host) -> Vec<String> {
+    match (&host.feed.error, host.connected) {
+        (Some(error), false) => unavailable(host, error),
+        (None, false) => unavailable(host, "connecting..."),
+
+        (_, true) => {
+            let (read, write) = host.disk_io();
+
+            vec![
+                host.name.clone(),
+                format!("{:.1}%", host.cpu()),
+                format!("{:.1}%", host.mem()),
+                format!("{:.2}", host.load()),
+                host.top_process().unwrap_or_default().to_string(),
+                format!("{}/s", MemPrefix::best_string(read)),
+                format!("{}/s", MemPrefix::best_string(write)),
+                sparkline(&host.cpu_history(), 100.0),
+            ]
+        }
+    }
+}
+
+/// A table summarizing every configured host.
+pub struct Overview<'b> {
+    /// The cells of every host in display order, whether it's highlighted and whether it's
+    /// unreachable.
+    rows: Vec<(Vec<String>, bool, bool)>,
+    column: HostColumn,
+    direction: SortDirection,
+    style: Style,
+    block: Option<Block<'b>>,
+    theme: Theme,
+    keys: KeyBindings,
+}
+
+impl<'b> Overview<'b> {
+    pub fn new(hosts: &Hosts, config: &Config) -> Self {
+        let rows = hosts
+            .sorted()
+            .into_iter()
+            .map(|i| {
+                let host = &hosts.hosts[i];
+                (cells(host), i == hosts.selected, host.feed.error.is_some())
+            })
+            .collect();
+
+        Self {
+            rows,
+            column: hosts.column,
+            direction: hosts.direction,
+            style: Default::default(),
+            block: Default::default(),
+            theme: config.theme.clone(),
+            keys: config.keys.clone(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> Overview {
+        Overview {
+            block: Some(block),
+            ..self
+        }
+    }
+}
+
+impl<'b> Widget for Overview<'b> {
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let header = COLUMNS.iter().map(|&(column, title)| match column {
+            Some(column) => {
+                let arrow = match (column == self.column, self.direction) {
+                    (false, _) => "",
+                    (true, SortDirection::Ascending) => "▲",
+                    (true, SortDirection::Descending) => "▼",
+                };
+
+                title_with_key(title, arrow, sort_action(column), &self.keys)
+            }
+            None => title.into(),
+        });
+
+        let bottom_title = format!(
+            " press {} to open a host ",
+            self.keys.hint(Action::OpenHost)
+        );
+
+        let row_style = Style::default().fg(*self.theme.table.row_color);
+        let error_style = Style::default().fg(*self.theme.bars.high_usage_color);
+
+        Table::new(self.rows.into_iter().map(|(cells, selected, unreachable)| {
+            let style = if unreachable { error_style } else { row_style };
+            let style = if selected {
+                style.add_modifier(Modifier::REVERSED)
+            } else {
+                style
+            };
+
+            Row::new(cells).style(style)
+        }))
+        .column_spacing(1)
+        .widths(&[
+            Constraint::Ratio(1, 6),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Percentage(25),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(SPARKLINE_LEN as u16),
+        ])
+        .block(
+            self.block
+                .unwrap_or_default()
+                .title(Title::from(bottom_title).position(tui::widgets::block::Position::Bottom)),
+        )
+        .style(self.style)
+        .header(
+            Row::new(header).style(
+                Style::default()
+                    .fg(*self.theme.table.header_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        )
+        .render(area, buf);
+    }
+}
@@ -0,0 +1,128 @@
+use tui::{
+    prelude::*,
+    widgets::{Block, Cell, Paragraph, Row, Table, Widget},
+};
+
+use crate::{
+    app::{App, Pressure as PressureInfo},
+    config::{BarsTheme, TableTheme},
+};
+
+use super::overview::{sparkline, SPARKLINE_LEN};
+
+/// The smallest stall share a full trend block stands for, so noise doesn't fill it.
+const MIN_TREND_SCALE: f64 = 10.0;
+
+/// Colors a stall share in percent. Stalls hurt long before a resource is saturated, so
+/// these thresholds are lower than the ones of usages.
+fn pressure_color(theme: &BarsTheme, share: f64) -> Color {
+    if share < 10.0 {
+        *theme.low_usage_color
+    } else if share < 40.0 {
+        *theme.medium_usage_color
+    } else {
+        *theme.high_usage_color
+    }
+}
+
+/// The share of time tasks were stalled on cpu, memory and io, with the trend of the 10
+/// second averages.
+pub struct Pressure<'a> {
+    pressure: Option<PressureInfo>,
+    trends: Vec<Vec<f64>>,
+    style: Style,
+    block: Option<Block<'a>>,
+    bars: BarsTheme,
+    theme: TableTheme,
+}
+
+impl<'a> Pressure<'a> {
+    pub fn new(app: &App) -> Self {
+        let history = app.displayed_history();
+
+        Self {
+            pressure: history.pressure,
+            trends: history
+                .pressure_avg10
+                .iter()
+                .map(|q| q.iter().copied().collect())
+                .collect(),
+            style: Default::default(),
+            block: Default::default(),
+            bars: app.config.theme.bars,
+            theme: app.config.theme.table,
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> Pressure {
+        Pressure {
+            block: Some(block),
+            ..self
+        }
+    }
+}
+
+impl<'a> Widget for Pressure<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = self.block.unwrap_or_default();
+
+        let Some(pressure) = self.pressure else {
+            let inner = block.inner(area);
+            block.render(area, buf);
+
+            Paragraph::new("no pressure stall information, it needs linux 4.20+ with psi enabled")
+                .alignment(Alignment::Center)
+                .wrap(Default::default())
+                .style(self.style)
+                .render(inner, buf);
+            return;
+        };
+
+        let share = |share: f64| {
+            Cell::from(format!("{share:.2}"))
+                .style(Style::default().fg(pressure_color(&self.bars, share)))
+        };
+
+        let rows = PressureInfo::NAMES
+            .iter()
+            .zip(pressure.stats())
+            .zip(&self.trends)
+            .map(|((name, stats), trend)| {
+                let max = trend.iter().copied().fold(MIN_TREND_SCALE, f64::max);
+
+                Row::new([
+                    Cell::from(*name).style(Style::default().fg(*self.theme.row_color)),
+                    share(stats.avg10),
+                    share(stats.avg60),
+                    share(stats.avg300),
+                    Cell::from(sparkline(trend, max))
+                        .style(Style::default().fg(pressure_color(&self.bars, stats.avg10))),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        Table::new(rows)
+            .column_spacing(1)
+            .widths(&[
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(SPARKLINE_LEN as u16),
+            ])
+            .block(block)
+            .style(self.style)
+            .header(
+                Row::new(["", "avg10", "avg60", "avg300", "trend"]).style(
+                    Style::default()
+                        .fg(*self.theme.header_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .render(area, buf);
+    }
+}
@@ -6,9 +6,11 @@ use tui::{
     widgets::{block::Title, Block, Row, Table, Widget},
 };
 
+use crossterm::event::KeyCode;
+
 use crate::{
     app::{App, InputState, MemPrefix, ProcessInfo},
-    config::TableTheme,
+    config::{Action, KeyBindings, TableTheme},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -97,42 +99,87 @@ impl Column {
         ""
     }
 
-    fn line_with_arrow(&self, sorting: &InputState) -> Line {
-        let arrow = self.sort_arrow_str(sorting).into();
-        let highlight_style = match sorting {
-            InputState::ProcessesSortSelection { .. } => {
-                Style::default().add_modifier(Modifier::UNDERLINED)
-            }
-            InputState::ProcessesSearch { .. } => Style::default(),
-        };
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Name => "name",
+            Column::Cpu => "cpu",
+            Column::Memory => "mem",
+            Column::DiskRead => "disk r/s",
+            Column::DiskWrite => "disk w/s",
+        }
+    }
 
+    pub fn sort_action(&self) -> Action {
         match self {
-            Column::Pid => vec![Span::styled("p", highlight_style), "id".into(), arrow],
-            Column::Name => vec![Span::styled("n", highlight_style), "ame".into(), arrow],
-            Column::Cpu => vec![Span::styled("c", highlight_style), "pu".into(), arrow],
-            Column::Memory => vec![Span::styled("m", highlight_style), "em".into(), arrow],
-            Column::DiskRead => vec![
-                "disk ".into(),
-                Span::styled("r", highlight_style),
-                "/s".into(),
-                arrow,
-            ],
-            Column::DiskWrite => vec![
-                "disk ".into(),
-                Span::styled("w", highlight_style),
-                "/s".into(),
-                arrow,
-            ],
+            Column::Pid => Action::SortPid,
+            Column::Name => Action::SortName,
+            Column::Cpu => Action::SortCpu,
+            Column::Memory => Action::SortMemory,
+            Column::DiskRead => Action::SortDiskRead,
+            Column::DiskWrite => Action::SortDiskWrite,
         }
-        .into()
+    }
+
+    fn line_with_arrow(&self, sorting: &InputState, keys: &KeyBindings) -> Line<'static> {
+        let arrow = self.sort_arrow_str(sorting);
+
+        let InputState::ProcessesSortSelection { .. } = sorting else {
+            return vec![self.title().into(), arrow.to_string().into()].into();
+        };
+
+        title_with_key(self.title(), arrow, self.sort_action(), keys)
     }
 }
 
+/// A column title with the key sorting by it highlighted in it, or appended to it when the
+/// key doesn't appear in the title.
+pub(super) fn title_with_key(
+    title: &'static str,
+    arrow: &str,
+    action: Action,
+    keys: &KeyBindings,
+) -> Line<'static> {
+    let arrow = arrow.to_string().into();
+    let highlight_style = Style::default().add_modifier(Modifier::UNDERLINED);
+
+    let key = keys.chords(action).iter().find_map(|c| match c.code {
+        KeyCode::Char(key) if c.is_plain_char() => Some(key),
+        _ => None,
+    });
+
+    let position = key.and_then(|key| {
+        title
+            .char_indices()
+            .find(|(_, c)| c.eq_ignore_ascii_case(&key))
+            .map(|(i, c)| (i, c.len_utf8()))
+    });
+
+    match (position, keys.chords(action).first()) {
+        (Some((i, len)), _) => vec![
+            title[..i].into(),
+            Span::styled(&title[i..i + len], highlight_style),
+            title[i + len..].into(),
+            arrow,
+        ],
+
+        (None, Some(chord)) => vec![
+            title.into(),
+            Span::styled(format!("({chord})"), highlight_style),
+            arrow,
+        ],
+
+        (None, None) => vec![title.into(), arrow],
+    }
+    .into()
+}
+
 pub struct Processes<'b> {
     processes: Vec<ProcessInfo>,
     style: Style,
     block: Option<Block<'b>>,
     theme: TableTheme,
+    keys: KeyBindings,
 
     sorting: InputState,
 }
@@ -140,10 +187,11 @@ pub struct Processes<'b> {
 impl<'b> Processes<'b> {
     pub fn new(app: &App) -> Self {
         Self {
-            processes: app.processes.clone(),
+            processes: app.displayed_processes().to_vec(),
             style: Default::default(),
             block: Default::default(),
             theme: app.config.theme.table,
+            keys: app.config.keys.clone(),
 
             sorting: app.input_state.clone(),
         }
@@ -184,7 +232,9 @@ impl<'b> Widget for Processes<'b> {
         }
 
         let bottom_title = match &self.sorting {
-            InputState::ProcessesSortSelection { .. } => " press / to search ".to_string(),
+            InputState::ProcessesSortSelection { .. } => {
+                format!(" press {} to search ", self.keys.hint(Action::Search))
+            }
             InputState::ProcessesSearch { search, .. } => format!(" searched: {search}_ "),
         };
 
@@ -208,7 +258,7 @@ impl<'b> Widget for Processes<'b> {
             Row::new(
                 Column::ALL_COLUMNS
                     .iter()
-                    .map(|c| c.line_with_arrow(&self.sorting)),
+                    .map(|c| c.line_with_arrow(&self.sorting, &self.keys)),
             )
             .style(
                 Style::default()
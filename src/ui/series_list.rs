@@ -0,0 +1,134 @@
+use tui::{
+    prelude::*,
+    widgets::{
+        block::{Position, Title},
+        Block, Clear, Paragraph, Widget,
+    },
+};
+
+use crate::{
+    app::App,
+    config::{Action, LegendPosition},
+};
+
+/// A popup listing the series of the focused chart, with the hidden ones unchecked.
+pub struct SeriesList<'a> {
+    series: Vec<(String, bool)>,
+    selected: usize,
+    legend: Option<LegendPosition>,
+    hints: String,
+    style: Style,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> SeriesList<'a> {
+    pub fn new(app: &App) -> Self {
+        let series = app
+            .series(app.focus)
+            .into_iter()
+            .map(|s| {
+                let hidden = app.is_hidden(app.focus, &s);
+                (s, !hidden)
+            })
+            .collect::<Vec<_>>();
+
+        let keys = &app.config.keys;
+        let hints = format!(
+            " {} toggle, {} solo, {} legend ",
+            keys.hint(Action::ToggleSeries),
+            keys.hint(Action::SoloSeries),
+            keys.hint(Action::CycleLegend),
+        );
+
+        Self {
+            selected: app
+                .series_list
+                .unwrap_or_default()
+                .min(series.len().saturating_sub(1)),
+            series,
+            legend: app.config.charts.get(app.focus).map(|o| o.legend),
+            hints,
+            style: Default::default(),
+            block: Default::default(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn block(self, block: Block) -> SeriesList {
+        SeriesList {
+            block: Some(block),
+            ..self
+        }
+    }
+}
+
+impl<'a> Widget for SeriesList<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text_width = self
+            .series
+            .iter()
+            .map(|(name, _)| name.len() + 4)
+            .chain([self.hints.len()])
+            .max()
+            .unwrap_or_default() as u16;
+        let width = (text_width + 2).min(area.width);
+        let height = (self.series.len() as u16 + 2).min(area.height);
+        let area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        // keeps the highlighted series in view
+        let rows = height.saturating_sub(2) as usize;
+        let offset = (self.selected + 1).saturating_sub(rows);
+
+        let lines = self
+            .series
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .map(|(i, (name, shown))| {
+                let line = format!("[{}] {name}", if *shown { 'x' } else { ' ' });
+
+                if i == self.selected {
+                    Line::styled(line, Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    Line::from(line)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut block = self
+            .block
+            .unwrap_or_default()
+            .title(Title::from(self.hints).position(Position::Bottom));
+        if let Some(legend) = self.legend {
+            block = block.title(
+                Title::from(format!(" legend: {} ", legend_name(legend)))
+                    .alignment(Alignment::Right),
+            );
+        }
+
+        Clear.render(area, buf);
+
+        Paragraph::new(lines)
+            .block(block)
+            .style(self.style)
+            .render(area, buf);
+    }
+}
+
+fn legend_name(position: LegendPosition) -> &'static str {
+    match position {
+        LegendPosition::TopRight => "top right",
+        LegendPosition::TopLeft => "top left",
+        LegendPosition::BottomLeft => "bottom left",
+        LegendPosition::BottomRight => "bottom right",
+        LegendPosition::Hidden => "hidden",
+    }
+}